@@ -0,0 +1,141 @@
+//! Power averaged over several packets.
+
+use std::collections::VecDeque;
+
+use crate::SparsnasPacket;
+
+/// Averages the power over the last few packets.
+///
+/// Each packet only carries the time between the two latest pulses,
+/// so the time for the pulses counted since the previous packet is
+/// estimated as the number of pulses times the time between pulses.
+/// The average is the total number of pulses over the total time,
+/// which weights each packet by the number of pulses it covers.
+///
+/// A pulse count lower than in the previous packet means the
+/// transmitter restarted, and the window is cleared.
+#[derive(Debug, Clone)]
+pub struct PowerAverager {
+    packets: VecDeque<SparsnasPacket>,
+    window: usize,
+}
+
+impl PowerAverager {
+    /// Create an averager over the last `window` packets. A window of
+    /// less than 2 packets is treated as 2, as the pulse count of two
+    /// packets is needed to get any pulses.
+    pub fn new(window: usize) -> Self {
+        let window = window.max(2);
+        PowerAverager {
+            packets: VecDeque::with_capacity(window),
+            window,
+        }
+    }
+
+    /// Add a packet to the window, dropping the oldest one if the
+    /// window is full. Packets with the same sequence number as the
+    /// latest one are ignored.
+    pub fn push(&mut self, pkt: &SparsnasPacket) {
+        if let Some(last) = self.packets.back() {
+            if last.packet_seq == pkt.packet_seq {
+                return;
+            }
+            if pkt.pulse_count < last.pulse_count {
+                self.packets.clear();
+            }
+        }
+        if self.packets.len() == self.window {
+            self.packets.pop_front();
+        }
+        self.packets.push_back(*pkt);
+    }
+
+    /// Forget all packets.
+    pub fn clear(&mut self) {
+        self.packets.clear();
+    }
+
+    /// Average power in W over the window.
+    ///
+    /// `pulses_per_kwh`: The number of pulses the meter gives per kWh. (usually 1000)
+    ///
+    /// Returns `None` if `pulses_per_kwh` is zero, or if there are no
+    /// pulses with a known time between pulses in the window.
+    pub fn average_power(&self, pulses_per_kwh: u32) -> Option<f64> {
+        if pulses_per_kwh == 0 {
+            return None;
+        }
+
+        let mut pulses = 0u64;
+        let mut ticks = 0u64;
+        for (prev, pkt) in self.packets.iter().zip(self.packets.iter().skip(1)) {
+            if pkt.time_between_pulses == 0 || pkt.is_standby() || pkt.is_idle() {
+                continue;
+            }
+            let p = (pkt.pulse_count - prev.pulse_count) as u64;
+            pulses += p;
+            ticks += p * pkt.time_between_pulses as u64;
+        }
+        if ticks == 0 {
+            return None;
+        }
+
+        Some(3686400000.0 * pulses as f64 / (pulses_per_kwh as f64 * ticks as f64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn packet(packet_seq: u16, pulse_count: u32, time_between_pulses: u16) -> SparsnasPacket {
+        SparsnasPacket::builder()
+            .serial(547040)
+            .packet_seq(packet_seq)
+            .pulse_count(pulse_count)
+            .time_between_pulses(time_between_pulses)
+            .build()
+    }
+
+    #[test]
+    fn average_power() {
+        let mut avg = PowerAverager::new(3);
+        assert_eq!(avg.average_power(1000), None);
+
+        avg.push(&packet(1, 1000, 1998));
+        assert_eq!(avg.average_power(1000), None);
+
+        // 2 pulses at 1 s interval
+        avg.push(&packet(2, 1002, 1024));
+        assert_eq!(avg.average_power(1000), Some(3600.0));
+
+        // 2 more pulses at 2 s interval, 4 pulses in 6 s
+        avg.push(&packet(3, 1004, 2048));
+        assert_eq!(avg.average_power(1000), Some(2400.0));
+        assert_eq!(avg.average_power(2000), Some(1200.0));
+        assert_eq!(avg.average_power(0), None);
+
+        // window is full, the first two pulses are dropped
+        avg.push(&packet(4, 1006, 2048));
+        assert_eq!(avg.average_power(1000), Some(1800.0));
+
+        avg.push(&packet(4, 2000, 1024));
+        assert_eq!(avg.average_power(1000), Some(1800.0));
+    }
+
+    #[test]
+    fn average_power_restart() {
+        let mut avg = PowerAverager::new(10);
+        avg.push(&packet(1, 1000, 1024));
+        avg.push(&packet(2, 1001, 1024));
+        assert_eq!(avg.average_power(1000), Some(3600.0));
+
+        avg.push(&packet(0, 0, 0));
+        assert_eq!(avg.average_power(1000), None);
+        avg.push(&packet(1, 1, 2048));
+        assert_eq!(avg.average_power(1000), Some(1800.0));
+
+        avg.clear();
+        assert_eq!(avg.average_power(1000), None);
+    }
+}
@@ -0,0 +1,160 @@
+//! Decoding base64 encoded packets.
+
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::{alphabet, Engine};
+
+use crate::{SparsnasDecodeError, SparsnasDecoder, SparsnasPacket};
+
+const CONFIG: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, CONFIG);
+const URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, CONFIG);
+
+/// Error decoding a packet from base64, see
+/// [SparsnasDecoder::decode_base64].
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DecodeBase64Error {
+    /// The string is neither standard nor URL-safe base64.
+    InvalidBase64,
+    /// The decoded data is neither 20 bytes (a packet) nor 18 bytes
+    /// (a packet without CRC).
+    WrongLength {
+        /// Number of decoded bytes.
+        got: usize,
+    },
+    /// The decoded bytes are not a valid packet.
+    DecodeError(SparsnasDecodeError),
+}
+
+impl From<SparsnasDecodeError> for DecodeBase64Error {
+    fn from(e: SparsnasDecodeError) -> Self {
+        DecodeBase64Error::DecodeError(e)
+    }
+}
+
+impl core::fmt::Display for DecodeBase64Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeBase64Error::InvalidBase64 => f.write_str("malformed base64 string"),
+            DecodeBase64Error::WrongLength { got } => {
+                write!(f, "expected 18 or 20 bytes in base64 string, got {got}")
+            }
+            DecodeBase64Error::DecodeError(e) => write!(f, "bad packet in base64 string: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeBase64Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeBase64Error::DecodeError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl SparsnasDecoder {
+    /// Decode a base64 encoded packet. Both the standard and the
+    /// URL-safe alphabet are accepted, with or without padding.
+    ///
+    /// 20 bytes are decoded using [SparsnasDecoder::decode], 18 bytes
+    /// as a packet without CRC using [SparsnasDecoder::decode_nocrc].
+    ///
+    /// Requires the `base64` feature.
+    pub fn decode_base64(&self, b64: &str) -> Result<SparsnasPacket, DecodeBase64Error> {
+        let b64 = b64.trim();
+        let data = STANDARD
+            .decode(b64)
+            .or_else(|_| URL_SAFE.decode(b64))
+            .map_err(|_| DecodeBase64Error::InvalidBase64)?;
+
+        if let Ok(data) = data.as_slice().try_into() {
+            Ok(self.decode(data)?)
+        } else if let Ok(data) = data.as_slice().try_into() {
+            Ok(self.decode_nocrc(data)?)
+        } else {
+            Err(DecodeBase64Error::WrongLength { got: data.len() })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+    use crate::*;
+
+    #[test]
+    fn decode_base64() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        let expected = d.decode(&KODARN).unwrap();
+
+        for b64 in [
+            "EUkkBw6idhcOz4aRZ0fPonfTbi0=",
+            "EUkkBw6idhcOz4aRZ0fPonfTbi0",
+            "EUkkBw6idhcOz4aRZ0fPonfTbi0=\n",
+            // without CRC
+            "EUkkBw6idhcOz4aRZ0fPonfT",
+        ] {
+            assert_eq!(d.decode_base64(b64), Ok(expected), "{b64}");
+        }
+
+        assert_eq!(
+            d.decode_base64("EUkkBw6idhcOz4aRZ0fPonfTbi0A"),
+            Err(DecodeBase64Error::WrongLength { got: 21 })
+        );
+        assert_eq!(
+            d.decode_base64("EUkkBw6idhcOz4aRZ0fPonfTbi0!"),
+            Err(DecodeBase64Error::InvalidBase64)
+        );
+        assert_eq!(
+            d.decode_base64("EUkkBw6idhcOz4aRZ0fPonfTbv8="),
+            Err(DecodeBase64Error::DecodeError(
+                SparsnasDecodeError::BadCRC {
+                    expected: 0x6e2d,
+                    got: 0x6eff
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn decode_base64_url_safe() {
+        use base64::engine::general_purpose::{STANDARD, URL_SAFE, URL_SAFE_NO_PAD};
+        use base64::Engine;
+
+        let d = SparsnasDecoder::new(REAL_SERIAL);
+        let mut pkt = d.decode(&REAL).unwrap();
+
+        let mut tested = 0;
+        for seq in 0..100 {
+            pkt.packet_seq = seq;
+            let data = d.encode(&pkt);
+            let url_safe = URL_SAFE.encode(data);
+            if url_safe == STANDARD.encode(data) {
+                continue;
+            }
+            assert_eq!(d.decode_base64(&url_safe), Ok(pkt));
+            assert_eq!(d.decode_base64(&URL_SAFE_NO_PAD.encode(data)), Ok(pkt));
+            assert_eq!(d.decode_base64(&STANDARD.encode(data)), Ok(pkt));
+            tested += 1;
+        }
+        assert!(tested > 0);
+    }
+
+    #[test]
+    fn decode_base64_error_display() {
+        assert_eq!(
+            DecodeBase64Error::WrongLength { got: 21 }.to_string(),
+            "expected 18 or 20 bytes in base64 string, got 21"
+        );
+        let err: &dyn std::error::Error =
+            &DecodeBase64Error::DecodeError(SparsnasDecodeError::BadSerial {
+                expected: 547040,
+                got: 565321,
+            });
+        assert!(err.source().is_some());
+    }
+}
@@ -0,0 +1,153 @@
+//! Building packets, e.g for tests.
+
+use crate::SparsnasPacket;
+
+/// Builder for a [SparsnasPacket], created by
+/// [SparsnasPacket::builder].
+///
+/// The serial number is the only field without a sensible default. It
+/// has to be set to the last 6 digits of the serial number of the
+/// decoder for [crate::SparsnasDecoder::decode] to accept the encoded
+/// packet. The other fields default to a transmitter that has just
+/// been powered on:
+///
+/// | field                 | default                                   |
+/// |-----------------------|-------------------------------------------|
+/// | `packet_seq`          | 0                                         |
+/// | `time_between_pulses` | 0xffff, see [SparsnasPacket::is_standby]  |
+/// | `pulse_count`         | 0                                         |
+/// | `battery_percentage`  | 100                                       |
+/// | `status`              | 0x40c1, as in all samples seen so far     |
+/// | `serial`              | 0                                         |
+/// | `raw_byte1`           | 0                                         |
+///
+/// ```
+/// use sparsnasdecode::{SparsnasDecoder, SparsnasPacket};
+///
+/// let pkt = SparsnasPacket::builder()
+///     .serial(565321)
+///     .packet_seq(36)
+///     .time_between_pulses(61392)
+///     .pulse_count(9)
+///     .build();
+///
+/// let d = SparsnasDecoder::new(400_565_321);
+/// assert_eq!(d.decode(&d.encode(&pkt)), Ok(pkt));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SparsnasPacketBuilder {
+    packet: SparsnasPacket,
+}
+
+impl SparsnasPacketBuilder {
+    /// Create a builder with all fields set to their defaults.
+    pub const fn new() -> Self {
+        SparsnasPacketBuilder {
+            packet: SparsnasPacket {
+                packet_seq: 0,
+                time_between_pulses: 0xffff,
+                pulse_count: 0,
+                battery_percentage: 100,
+                status: 0x40c1,
+                serial: 0,
+                raw_byte1: 0,
+            },
+        }
+    }
+
+    /// Set [SparsnasPacket::packet_seq].
+    pub const fn packet_seq(mut self, packet_seq: u16) -> Self {
+        self.packet.packet_seq = packet_seq;
+        self
+    }
+
+    /// Set [SparsnasPacket::time_between_pulses].
+    pub const fn time_between_pulses(mut self, time_between_pulses: u16) -> Self {
+        self.packet.time_between_pulses = time_between_pulses;
+        self
+    }
+
+    /// Set [SparsnasPacket::pulse_count].
+    pub const fn pulse_count(mut self, pulse_count: u32) -> Self {
+        self.packet.pulse_count = pulse_count;
+        self
+    }
+
+    /// Set [SparsnasPacket::battery_percentage].
+    pub const fn battery_percentage(mut self, battery_percentage: u8) -> Self {
+        self.packet.battery_percentage = battery_percentage;
+        self
+    }
+
+    /// Set [SparsnasPacket::status].
+    pub const fn status(mut self, status: u16) -> Self {
+        self.packet.status = status;
+        self
+    }
+
+    /// Set [SparsnasPacket::serial], the last 6 digits of the serial
+    /// number.
+    pub const fn serial(mut self, serial: u32) -> Self {
+        self.packet.serial = serial;
+        self
+    }
+
+    /// Set [SparsnasPacket::raw_byte1].
+    pub const fn raw_byte1(mut self, raw_byte1: u8) -> Self {
+        self.packet.raw_byte1 = raw_byte1;
+        self
+    }
+
+    /// The packet.
+    pub const fn build(self) -> SparsnasPacket {
+        self.packet
+    }
+}
+
+impl Default for SparsnasPacketBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SparsnasPacket {
+    /// Create a [SparsnasPacketBuilder] with default values.
+    pub const fn builder() -> SparsnasPacketBuilder {
+        SparsnasPacketBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+    use crate::*;
+
+    #[test]
+    fn defaults() {
+        let pkt = SparsnasPacket::builder().build();
+
+        assert_eq!(pkt.packet_seq, 0);
+        assert_eq!(pkt.battery_percentage, 100);
+        assert_eq!(pkt.status, 0x40c1);
+        assert!(pkt.is_standby());
+        assert_eq!(pkt, SparsnasPacketBuilder::default().build());
+    }
+
+    #[test]
+    fn build() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        let kodarn = d.decode(&KODARN).unwrap();
+
+        const PKT: SparsnasPacket = SparsnasPacket::builder()
+            .packet_seq(36)
+            .time_between_pulses(61392)
+            .pulse_count(9)
+            .battery_percentage(100)
+            .status(0x40c1)
+            .serial(565321)
+            .raw_byte1(0x49)
+            .build();
+
+        assert_eq!(PKT, kodarn);
+    }
+}
@@ -0,0 +1,79 @@
+//! Decoding using the standard conversion traits.
+
+use crate::{SparsnasDecodeError, SparsnasDecoder, SparsnasPacket};
+
+/// A reference to a decoder, see [SparsnasDecoder::context].
+#[derive(Debug, Clone, Copy)]
+pub struct DecoderContext<'a> {
+    decoder: &'a SparsnasDecoder,
+}
+
+/// Data to decode together with the decoder to decode it with, see
+/// [DecoderContext::with_data]. Convert it to a [SparsnasPacket]
+/// using [TryFrom].
+#[derive(Debug, Clone, Copy)]
+pub struct DecoderInput<'a> {
+    decoder: &'a SparsnasDecoder,
+    data: &'a [u8],
+}
+
+impl SparsnasDecoder {
+    /// Get a context for decoding using [TryFrom], e.g
+    /// `SparsnasPacket::try_from(decoder.context().with_data(&buf))?`.
+    pub fn context(&self) -> DecoderContext<'_> {
+        DecoderContext { decoder: self }
+    }
+}
+
+impl<'a> DecoderContext<'a> {
+    /// Pair the decoder with data to decode.
+    pub fn with_data(self, data: &'a [u8]) -> DecoderInput<'a> {
+        DecoderInput {
+            decoder: self.decoder,
+            data,
+        }
+    }
+
+    /// Decode a packet, same as [SparsnasDecoder::decode_slice].
+    pub fn decode(self, data: &[u8]) -> Result<SparsnasPacket, SparsnasDecodeError> {
+        self.decoder.decode_slice(data)
+    }
+}
+
+impl TryFrom<DecoderInput<'_>> for SparsnasPacket {
+    type Error = SparsnasDecodeError;
+
+    /// Same as [SparsnasDecoder::decode_slice].
+    fn try_from(input: DecoderInput<'_>) -> Result<Self, Self::Error> {
+        input.decoder.decode_slice(input.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+    use crate::*;
+
+    fn decode(d: &SparsnasDecoder, buf: &[u8]) -> Result<u16, SparsnasDecodeError> {
+        let pkt = SparsnasPacket::try_from(d.context().with_data(buf))?;
+        Ok(pkt.packet_seq)
+    }
+
+    #[test]
+    fn try_from() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+
+        assert_eq!(decode(&d, &KODARN), Ok(36));
+        assert_eq!(
+            decode(&d, &KODARN[0..19]),
+            Err(SparsnasDecodeError::BadLength {
+                expected: 20,
+                got: 19
+            })
+        );
+        assert_eq!(d.context().decode(&KODARN), d.decode(&KODARN));
+
+        let pkt: Result<SparsnasPacket, _> = d.context().with_data(&KODARN).try_into();
+        assert_eq!(pkt, d.decode(&KODARN));
+    }
+}
@@ -0,0 +1,50 @@
+//! Formatting packets as CSV.
+
+use crate::SparsnasPacket;
+
+impl SparsnasPacket {
+    /// Header line matching [SparsnasPacket::to_csv_row].
+    ///
+    /// Requires the `csv` feature.
+    pub fn csv_header() -> &'static str {
+        "seq,serial,power_w,pulse_count,battery_pct,status"
+    }
+
+    /// Format the packet as a line of comma separated values, without
+    /// line terminator, e.g `20395,547040,1845,4555342,100,16577`.
+    ///
+    /// The power is 0 if there is none, see [SparsnasPacket::power].
+    ///
+    /// Requires the `csv` feature.
+    pub fn to_csv_row(&self, pulses_per_kwh: u32) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.packet_seq,
+            self.serial,
+            self.power(pulses_per_kwh).unwrap_or(0),
+            self.pulse_count,
+            self.battery_percentage,
+            self.status
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+    use crate::*;
+
+    #[test]
+    fn csv_row() {
+        let mut pkt = REAL_PACKET;
+
+        assert_eq!(
+            SparsnasPacket::csv_header(),
+            "seq,serial,power_w,pulse_count,battery_pct,status"
+        );
+        assert_eq!(pkt.to_csv_row(1000), "20395,547040,1845,4555342,100,16577");
+
+        pkt.time_between_pulses = 0xffff;
+        assert_eq!(pkt.to_csv_row(1000), "20395,547040,0,4555342,100,16577");
+    }
+}
@@ -0,0 +1,160 @@
+//! Dropping retransmitted packets.
+
+use crate::SparsnasPacket;
+
+impl SparsnasPacket {
+    /// True if this is a retransmission of `other`, i.e it has the
+    /// same sequence number, serial number, pulse count and time
+    /// between pulses.
+    pub fn is_duplicate_of(&self, other: &SparsnasPacket) -> bool {
+        self.packet_seq == other.packet_seq
+            && self.serial == other.serial
+            && self.pulse_count == other.pulse_count
+            && self.time_between_pulses == other.time_between_pulses
+    }
+}
+
+/// Remembers the last `N` accepted packets, to drop retransmissions
+/// of them. Works for packets from several transmitters.
+///
+/// Packets are compared using [SparsnasPacket::is_duplicate_of], so
+/// a packet 65536 packets later, with the same sequence number after
+/// it wrapped, is not taken for a duplicate unless the pulse count and
+/// time between pulses also are the same. Normally it would have been
+/// forgotten long before that anyway.
+#[derive(Debug, Clone)]
+pub struct DuplicateFilter<const N: usize> {
+    recent: [Option<SparsnasPacket>; N],
+    next: usize,
+}
+
+impl<const N: usize> DuplicateFilter<N> {
+    /// Create a filter which has not seen any packets.
+    pub const fn new() -> Self {
+        DuplicateFilter {
+            recent: [None; N],
+            next: 0,
+        }
+    }
+
+    /// Check if a packet is new. Returns `false` if it is a duplicate
+    /// of one of the last `N` accepted packets, otherwise remembers
+    /// it and returns `true`.
+    pub fn accept(&mut self, pkt: &SparsnasPacket) -> bool {
+        if N == 0 {
+            return true;
+        }
+        if self.recent.iter().flatten().any(|p| pkt.is_duplicate_of(p)) {
+            return false;
+        }
+        self.recent[self.next] = Some(*pkt);
+        self.next = (self.next + 1) % N;
+        true
+    }
+
+    /// Forget all packets.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<const N: usize> Default for DuplicateFilter<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+    use crate::*;
+
+    #[test]
+    fn is_duplicate_of() {
+        let pkt = REAL_PACKET;
+        let mut other = pkt;
+        assert!(pkt.is_duplicate_of(&other));
+
+        other.battery_percentage = 99;
+        assert!(pkt.is_duplicate_of(&other));
+
+        let mut other = pkt;
+        other.serial = 565321;
+        assert!(!pkt.is_duplicate_of(&other));
+
+        let mut other = pkt;
+        other.packet_seq += 1;
+        assert!(!pkt.is_duplicate_of(&other));
+
+        let mut other = pkt;
+        other.pulse_count += 1;
+        assert!(!pkt.is_duplicate_of(&other));
+    }
+
+    #[test]
+    fn duplicate_filter() {
+        let mut filter = DuplicateFilter::<2>::new();
+        let first = SparsnasPacket::builder()
+            .serial(547040)
+            .packet_seq(10)
+            .pulse_count(100)
+            .build();
+        let next = SparsnasPacket::builder()
+            .serial(547040)
+            .packet_seq(11)
+            .pulse_count(101)
+            .build();
+        let other = SparsnasPacket::builder()
+            .serial(565321)
+            .packet_seq(10)
+            .pulse_count(5)
+            .build();
+
+        assert!(filter.accept(&first));
+        assert!(!filter.accept(&first));
+        assert!(filter.accept(&other));
+        assert!(!filter.accept(&first));
+        assert!(!filter.accept(&other));
+
+        // the first packet is forgotten
+        assert!(filter.accept(&next));
+        assert!(filter.accept(&first));
+
+        filter.clear();
+        assert!(filter.accept(&next));
+    }
+
+    #[test]
+    fn duplicate_filter_wrap() {
+        let mut filter = DuplicateFilter::<4>::default();
+        let mut pkt = SparsnasPacket::builder()
+            .serial(547040)
+            .packet_seq(0xffff)
+            .pulse_count(100)
+            .build();
+
+        let mut wrapped = pkt;
+        wrapped.packet_seq = 0;
+
+        assert!(filter.accept(&pkt));
+        assert!(filter.accept(&wrapped));
+        assert!(!filter.accept(&pkt));
+
+        // same sequence number after wrapping, more pulses
+        pkt.pulse_count = 5000;
+        assert!(filter.accept(&pkt));
+    }
+
+    #[test]
+    fn duplicate_filter_empty() {
+        let mut filter = DuplicateFilter::<0>::new();
+        let pkt = SparsnasPacket::builder()
+            .serial(547040)
+            .packet_seq(10)
+            .pulse_count(100)
+            .build();
+
+        assert!(filter.accept(&pkt));
+        assert!(filter.accept(&pkt));
+    }
+}
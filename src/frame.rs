@@ -0,0 +1,200 @@
+//! Packets which have been checked for length and CRC, but not yet
+//! decrypted.
+
+use core::ops::Range;
+
+use crate::{SparsnasDecodeError, SparsnasDecoder, SparsnasPacket};
+
+/// The fields of the 18 byte packet (without CRC), for annotating
+/// dumps of e.g [SparsnasDecoder::decode_to_plaintext].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameField {
+    /// Length byte, always 17.
+    Length,
+    /// Unencrypted, lowest byte of the serial number.
+    Byte1,
+    /// Unencrypted, low 7 bits of the sequence number.
+    SeqByte,
+    /// Status word.
+    Status,
+    /// Last 6 digits of the serial number.
+    Serial,
+    /// Sequence number.
+    PacketSeq,
+    /// Time between pulses.
+    TimeBetweenPulses,
+    /// Pulse count.
+    PulseCount,
+    /// Battery percentage.
+    Battery,
+}
+
+impl FrameField {
+    /// All fields, in the order they appear in the packet.
+    pub const ALL: [FrameField; 9] = [
+        FrameField::Length,
+        FrameField::Byte1,
+        FrameField::SeqByte,
+        FrameField::Status,
+        FrameField::Serial,
+        FrameField::PacketSeq,
+        FrameField::TimeBetweenPulses,
+        FrameField::PulseCount,
+        FrameField::Battery,
+    ];
+
+    /// Byte range of the field in the packet.
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            FrameField::Length => 0..1,
+            FrameField::Byte1 => 1..2,
+            FrameField::SeqByte => 2..3,
+            FrameField::Status => 3..5,
+            FrameField::Serial => 5..9,
+            FrameField::PacketSeq => 9..11,
+            FrameField::TimeBetweenPulses => 11..13,
+            FrameField::PulseCount => 13..17,
+            FrameField::Battery => 17..18,
+        }
+    }
+
+    /// Name of the field, as the corresponding [SparsnasPacket] field
+    /// where there is one.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FrameField::Length => "length",
+            FrameField::Byte1 => "raw_byte1",
+            FrameField::SeqByte => "seq_byte",
+            FrameField::Status => "status",
+            FrameField::Serial => "serial",
+            FrameField::PacketSeq => "packet_seq",
+            FrameField::TimeBetweenPulses => "time_between_pulses",
+            FrameField::PulseCount => "pulse_count",
+            FrameField::Battery => "battery_percentage",
+        }
+    }
+}
+
+/// A packet with valid length and CRC. Checking this does not need
+/// the key, so it can be done before knowing which transmitter the
+/// packet is from. Decrypt it using [SparsnasDecoder::decode_frame].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SparsnasRawFrame {
+    data: [u8; 18],
+}
+
+impl SparsnasRawFrame {
+    /// Check length and CRC of a 20 byte packet. Both a slice of
+    /// another size and a length byte other than 17 are reported as
+    /// [SparsnasDecodeError::BadLength], see there.
+    pub fn parse(data: &[u8]) -> Result<Self, SparsnasDecodeError> {
+        let data: &[u8; 20] = data
+            .try_into()
+            .map_err(|_| SparsnasDecodeError::BadLength {
+                expected: 20,
+                got: data.len(),
+            })?;
+        if data[0] != 17 {
+            return Err(SparsnasDecodeError::BadLength {
+                expected: 17,
+                got: data[0] as usize,
+            });
+        }
+        crate::check_crc(data)?;
+        Ok(SparsnasRawFrame {
+            data: data[0..18].try_into().unwrap(),
+        })
+    }
+
+    /// The packet, without CRC.
+    pub fn data(&self) -> &[u8; 18] {
+        &self.data
+    }
+}
+
+impl SparsnasDecoder {
+    /// Decrypt a packet without CRC, without checking anything. The
+    /// fields can be found using [FrameField].
+    pub fn decode_to_plaintext(&self, data: &[u8; 18]) -> [u8; 18] {
+        let mut plain = *data;
+        self.xor_crypt(0, &mut plain);
+        plain
+    }
+
+    /// Decrypt and decode a frame which has already been checked for
+    /// length and CRC.
+    pub fn decode_frame(
+        &self,
+        frame: &SparsnasRawFrame,
+    ) -> Result<SparsnasPacket, SparsnasDecodeError> {
+        self.decode_nocrc(frame.data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+    use crate::*;
+
+    #[test]
+    fn parse() {
+        let frame = SparsnasRawFrame::parse(&KODARN).unwrap();
+        assert_eq!(frame.data()[..], KODARN[0..18]);
+
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        assert_eq!(d.decode_frame(&frame), d.decode(&KODARN));
+
+        let other = SparsnasDecoder::new(REAL_SERIAL);
+        assert!(other.decode_frame(&frame).is_err());
+    }
+
+    #[test]
+    fn plaintext() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        let plain = d.decode_to_plaintext(nocrc(&KODARN));
+
+        assert_eq!(plain, KODARN_PLAINTEXT);
+        assert_eq!(plain[FrameField::Serial.range()], [0x00, 0x08, 0xa0, 0x49]);
+        assert_eq!(plain, d.decode(&KODARN).unwrap().raw_plaintext());
+
+        // works on packets from other transmitters, but gives garbage
+        let other = SparsnasDecoder::new(REAL_SERIAL);
+        let plain = other.decode_to_plaintext(nocrc(&KODARN));
+        assert_ne!(plain[FrameField::Serial.range()], [0x00, 0x08, 0xa0, 0x49]);
+
+        let mut end = 0;
+        for field in FrameField::ALL {
+            assert_eq!(field.range().start, end);
+            end = field.range().end;
+        }
+        assert_eq!(end, 18);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            SparsnasRawFrame::parse(&KODARN[0..19]),
+            Err(SparsnasDecodeError::BadLength {
+                expected: 20,
+                got: 19
+            })
+        );
+
+        let mut bad = KODARN;
+        bad[19] ^= 1;
+        assert!(matches!(
+            SparsnasRawFrame::parse(&bad),
+            Err(SparsnasDecodeError::BadCRC { .. })
+        ));
+
+        let mut bad = KODARN;
+        bad[0] = 18;
+        assert_eq!(
+            SparsnasRawFrame::parse(&bad),
+            Err(SparsnasDecodeError::BadLength {
+                expected: 17,
+                got: 18
+            })
+        );
+    }
+}
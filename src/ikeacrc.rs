@@ -1,5 +1,5 @@
-/// This implements crc16 with parameters used by the sparsnas transmitter
-/// (polynomial 0x8005 and init value 0xffff)
+//! This implements crc16 with parameters used by the sparsnas transmitter
+//! (polynomial 0x8005 and init value 0xffff)
 
 const TABLE: [u16; 256] = [
     0x0000, 0x8005, 0x800f, 0x000a, 0x801b, 0x001e, 0x0014, 0x8011, 0x8033, 0x0036, 0x003c, 0x8039,
@@ -26,6 +26,19 @@ const TABLE: [u16; 256] = [
     0x0208, 0x820d, 0x8207, 0x0202,
 ];
 
+/// Calculate the CRC of `data`. For a packet the CRC is calculated
+/// over the first 18 bytes and stored big endian in the last two.
+///
+/// ```
+/// let packet = [
+///     0x11, 0xe0, 0x2b, 0x07, 0x0e, 0xa2, 0x1d, 0x28, 0xa7, 0x80, 0x09, 0x12, 0xbe, 0x47,
+///     0x8a, 0x20, 0x5b, 0x14, 0x69, 0x57,
+/// ];
+///
+/// let crc = sparsnasdecode::crc(&packet[0..18]);
+/// assert_eq!(crc, 0x6957);
+/// assert_eq!(crc.to_be_bytes(), packet[18..20]);
+/// ```
 pub fn crc(data: &[u8]) -> u16 {
     let mut crc: u16 = 0xeaa8;
     for b in data {
@@ -48,6 +61,12 @@ mod tests {
         assert_eq!(crc, 0xaaed);
     }
 
+    #[test]
+    fn empty() {
+        let crc = crate::ikeacrc::crc(&[]);
+        assert_eq!(crc, 0xffff);
+    }
+
     #[test]
     fn zeros() {
         let testdata = [0x00];
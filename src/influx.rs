@@ -0,0 +1,63 @@
+//! Formatting packets as InfluxDB line protocol.
+
+use std::fmt::Write;
+
+use crate::SparsnasPacket;
+
+impl SparsnasPacket {
+    /// Format the packet as a line of InfluxDB line protocol, e.g
+    /// `energy,serial=565321 power_w=60,pulse_count=9i,battery_pct=100i 1700000000000000000`.
+    ///
+    /// The serial number is a tag. `power_w` is left out if there is
+    /// no power, see [SparsnasPacket::power]. Spaces, commas and
+    /// backslashes in `measurement` are escaped. `timestamp_ns` is in
+    /// nanoseconds since the Unix epoch.
+    ///
+    /// Requires the `influx` feature.
+    pub fn to_influx_line(
+        &self,
+        measurement: &str,
+        pulses_per_kwh: u32,
+        timestamp_ns: u64,
+    ) -> String {
+        let mut line = String::new();
+        for c in measurement.chars() {
+            if matches!(c, ' ' | ',' | '\\') {
+                line.push('\\');
+            }
+            line.push(c);
+        }
+        write!(line, ",serial={} ", self.serial).unwrap();
+        if let Some(power) = self.power(pulses_per_kwh) {
+            write!(line, "power_w={power},").unwrap();
+        }
+        write!(
+            line,
+            "pulse_count={}i,battery_pct={}i {timestamp_ns}",
+            self.pulse_count, self.battery_percentage
+        )
+        .unwrap();
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+
+    #[test]
+    fn influx_line() {
+        let mut pkt = KODARN_PACKET;
+
+        assert_eq!(
+            pkt.to_influx_line("energy", 1000, 1700000000000000000),
+            "energy,serial=565321 power_w=60,pulse_count=9i,battery_pct=100i 1700000000000000000"
+        );
+
+        pkt.time_between_pulses = 0;
+        assert_eq!(
+            pkt.to_influx_line("my energy,1", 1000, 1),
+            "my\\ energy\\,1,serial=565321 pulse_count=9i,battery_pct=100i 1"
+        );
+    }
+}
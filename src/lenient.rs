@@ -0,0 +1,138 @@
+//! Decoding packets while collecting everything that looks wrong,
+//! for diagnostics.
+
+use crate::{SparsnasDecodeError, SparsnasDecoder, SparsnasPacket};
+
+/// Result of [SparsnasDecoder::decode_lenient].
+#[derive(Debug, PartialEq)]
+pub struct Decoded {
+    /// The decrypted packet, if there were enough bytes to decrypt
+    /// it. This is set even if there are issues.
+    pub packet: Option<SparsnasPacket>,
+    /// Everything that failed validation, in the order the checks
+    /// are made by [SparsnasDecoder::decode]. Empty if the packet
+    /// would have been accepted by it.
+    pub issues: Vec<SparsnasDecodeError>,
+}
+
+impl Decoded {
+    /// True if there are no issues.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl SparsnasDecoder {
+    /// Decode a packet like [SparsnasDecoder::decode], but instead of
+    /// stopping at the first problem, decrypt as much as possible and
+    /// report all problems found.
+    ///
+    /// The packet is decrypted if `data` is at least 18 bytes (length
+    /// byte and payload), even if the length byte, CRC, sequence byte
+    /// or serial number is wrong. The CRC is only checked if `data`
+    /// is exactly 20 bytes.
+    pub fn decode_lenient(&self, data: &[u8]) -> Decoded {
+        let mut issues = Vec::new();
+
+        if data.len() != 20 {
+            issues.push(SparsnasDecodeError::BadLength {
+                expected: 20,
+                got: data.len(),
+            });
+        } else if let Err(e) = crate::check_crc(data.try_into().unwrap()) {
+            issues.push(e);
+        }
+
+        let Some(data) = data.get(0..18) else {
+            return Decoded {
+                packet: None,
+                issues,
+            };
+        };
+
+        if data[0] != 17 {
+            issues.push(SparsnasDecodeError::BadLength {
+                expected: 17,
+                got: data[0] as usize,
+            });
+        }
+
+        let pkt = self.decrypt_nocrclen(data[1..18].try_into().unwrap());
+
+        let expected = (pkt.packet_seq & 0x7f) as u8;
+        if expected != data[2] {
+            issues.push(SparsnasDecodeError::BadPacketCount {
+                expected,
+                got: data[2],
+            });
+        }
+
+        let expected = self.serial() % 1_000_000;
+        if pkt.serial != expected {
+            issues.push(SparsnasDecodeError::BadSerial {
+                expected,
+                got: pkt.serial,
+            });
+        }
+
+        Decoded {
+            packet: Some(pkt),
+            issues,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+    use crate::*;
+
+    #[test]
+    fn lenient_ok() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        let decoded = d.decode_lenient(&KODARN);
+        assert!(decoded.is_ok());
+        assert_eq!(decoded.packet, Some(KODARN_PACKET));
+    }
+
+    #[test]
+    fn lenient_issues() {
+        let d = SparsnasDecoder::new(REAL_SERIAL);
+
+        let mut data = KODARN;
+        data[2] ^= 1;
+        let decoded = d.decode_lenient(&data);
+        assert_eq!(
+            decoded.issues,
+            [
+                SparsnasDecodeError::BadCRC {
+                    expected: 0xe828,
+                    got: 0x6e2d
+                },
+                SparsnasDecodeError::BadPacketCount {
+                    expected: 36,
+                    got: 37
+                },
+                SparsnasDecodeError::BadSerial {
+                    expected: 547040,
+                    got: 6514505
+                },
+            ]
+        );
+        assert!(decoded.packet.is_some());
+
+        let decoded = d.decode_lenient(&KODARN[0..18]);
+        assert_eq!(
+            decoded.issues[0],
+            SparsnasDecodeError::BadLength {
+                expected: 20,
+                got: 18
+            }
+        );
+        assert!(decoded.packet.is_some());
+
+        let decoded = d.decode_lenient(&KODARN[0..10]);
+        assert_eq!(decoded.issues.len(), 1);
+        assert_eq!(decoded.packet, None);
+    }
+}
@@ -2,15 +2,110 @@
 //!
 //! See <https://github.com/kodarn/Sparsnas> for a very detailed
 //! reverse engineering of the protocol.
+//!
+//! The crate is `no_std` when the default `std` feature is disabled.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "std")]
+mod average;
+#[cfg(feature = "base64")]
+mod b64;
+mod builder;
+mod context;
+#[cfg(feature = "csv")]
+mod csv;
+mod dedup;
+mod frame;
+pub mod ikeacrc;
+#[cfg(feature = "influx")]
+mod influx;
+#[cfg(feature = "std")]
+mod lenient;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "std")]
+mod multi;
+mod packets;
+#[cfg(feature = "std")]
+mod recover;
+mod session;
+mod status;
+#[cfg(test)]
+mod testdata;
+
+#[cfg(feature = "std")]
+pub use average::PowerAverager;
+#[cfg(feature = "base64")]
+pub use b64::DecodeBase64Error;
+pub use builder::SparsnasPacketBuilder;
+pub use context::{DecoderContext, DecoderInput};
+pub use dedup::DuplicateFilter;
+pub use frame::{FrameField, SparsnasRawFrame};
+pub use ikeacrc::crc;
+#[cfg(feature = "std")]
+pub use lenient::Decoded;
+#[cfg(feature = "std")]
+pub use multi::MultiDecoder;
+pub use packets::SparsnasPackets;
+pub use session::{seq_distance, SeqStatus, SparsnasSession, PULSE_COUNT_RESET_THRESHOLD};
+pub use status::SparsnasStatus;
+
+/// Tick rate of the transmitter's timer, in Hz. The
+/// [SparsnasPacket::time_between_pulses] field is measured in ticks,
+/// i.e 1/1024 seconds.
+pub const TIMER_HZ: u32 = 1024;
+
+/// Battery percentage below which [SparsnasPacket::battery_is_low]
+/// is true.
+pub const DEFAULT_LOW_BATTERY_THRESHOLD: u8 = 20;
+
+/// Battery percentage below which
+/// [SparsnasPacket::battery_is_critical] is true.
+pub const CRITICAL_BATTERY_THRESHOLD: u8 = 10;
 
-mod ikeacrc;
+/// Value of [SparsnasPacket::time_between_pulses] when no pulse has
+/// been seen for as long as the timer can count, 0xffff ticks or about
+/// 64 seconds, see [SparsnasPacket::has_recent_pulse].
+pub const TIME_BETWEEN_PULSES_SATURATED: u16 = 0xffff;
 
+/// Value added to the serial number to derive the key, see
+/// [SparsnasDecoder::key_for_serial].
+pub const KEY_DERIVATION_MAGIC: u32 = 0x8AEF9335;
+
+/// Pulses per kWh used by a [SparsnasDecoder] unless set with
+/// [SparsnasDecoder::with_pulses_per_kwh]. Most meters give 1000.
+pub const DEFAULT_PULSES_PER_KWH: u32 = 1000;
+
+#[derive(Clone, Copy)]
 pub struct SparsnasDecoder {
     serial: u32,
     key: [u8; 5],
+    pulses_per_kwh: u32,
+    check_serial: bool,
+    check_seq: bool,
 }
 
-#[derive(Debug, PartialEq)]
+impl core::fmt::Debug for SparsnasDecoder {
+    /// The key is left out, so the output can be shared without
+    /// giving away how to decode the transmitter's packets.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SparsnasDecoder")
+            .field("serial", &self.serial)
+            .field("key", &format_args!("[REDACTED]"))
+            .field("pulses_per_kwh", &self.pulses_per_kwh)
+            .field("check_serial", &self.check_serial)
+            .field("check_seq", &self.check_seq)
+            .finish()
+    }
+}
+
+/// A decoded packet.
+///
+/// With the `serde` feature enabled this serializes as a struct with
+/// the field names as they are spelled here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SparsnasPacket {
     /// Sequence number for this packet. The transmitter increments
     /// this for each packet it sends.
@@ -22,30 +117,572 @@ pub struct SparsnasPacket {
     /// Total number of pulses (blinks) transmitter has seen since poweron.
     pub pulse_count: u32,
 
-    ///
+    /// Remaining battery level of the transmitter, in percent.
     pub battery_percentage: u8,
 
-    ///
+    /// Status word. The meaning of the individual bits is not fully
+    /// known. See [SparsnasPacket::status_flags].
     pub status: u16,
 
     /// This is the last 6 (decimal) digits of the serial number of the transmitter.
     pub serial: u32,
+
+    /// Unencrypted byte 1 of the packet. The transmitter puts the
+    /// lowest byte of its full serial number here.
+    pub raw_byte1: u8,
+}
+
+/// A decoded packet together with metadata from the receiver, e.g
+/// RSSI. See [SparsnasDecoder::decode_with_meta].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodedFrame<M> {
+    /// The decoded packet.
+    pub packet: SparsnasPacket,
+    /// Metadata supplied when decoding.
+    pub meta: M,
+}
+
+/// Battery state of the transmitter, see [SparsnasPacket::battery_state].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BatteryState {
+    /// [DEFAULT_LOW_BATTERY_THRESHOLD] (20%) or more.
+    Ok,
+    /// Below [DEFAULT_LOW_BATTERY_THRESHOLD] (20%).
+    Low,
+    /// Below [CRITICAL_BATTERY_THRESHOLD] (10%).
+    Critical,
+}
+
+/// How to round the power to whole watts, see
+/// [SparsnasPacket::power_with].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rounding {
+    /// Round down, as [SparsnasPacket::power] does.
+    #[default]
+    Floor,
+    /// Round to the nearest watt, halves up.
+    Nearest,
+    /// Round up.
+    Ceil,
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum SparsnasDecodeError {
-    BadCRC,
-    BadLength,
-    BadSerial,
-    BadPacketCount,
+    /// The CRC at the end of the packet does not match the data.
+    BadCRC {
+        /// CRC calculated over the received data.
+        expected: u16,
+        /// CRC carried in the packet.
+        got: u16,
+    },
+    /// The packet length is wrong. Either the length byte at the
+    /// start of the packet is not 17, reported with `expected` 17 and
+    /// the length byte as `got`, or a slice passed to e.g
+    /// [SparsnasDecoder::decode_slice] has the wrong size, reported
+    /// with `expected` 18 or 20 and the size of the slice as `got`.
+    BadLength {
+        /// Length byte or number of bytes required.
+        expected: usize,
+        /// Length byte or number of bytes seen.
+        got: usize,
+    },
+    /// The packet is from a transmitter with another serial number,
+    /// or was decrypted with the wrong key.
+    BadSerial {
+        /// Last 6 digits of the serial number of the decoder.
+        expected: u32,
+        /// Serial number decrypted from the packet.
+        got: u32,
+    },
+    /// The unencrypted sequence byte does not match the decrypted
+    /// sequence number.
+    BadPacketCount {
+        /// Low 7 bits of the decrypted sequence number.
+        expected: u8,
+        /// Unencrypted sequence byte.
+        got: u8,
+    },
+    /// The packet is valid, but none of the decoders of a
+    /// `MultiDecoder` decoded it.
+    UnknownTransmitter {
+        /// Serial number decrypted from the packet by a decoder which
+        /// got a consistent sequence number, if there was one.
+        serial: Option<u32>,
+    },
+}
+
+impl core::fmt::Display for SparsnasDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SparsnasDecodeError::BadCRC { expected, got } => {
+                write!(f, "CRC mismatch: expected {expected:#06x}, got {got:#06x}")
+            }
+            SparsnasDecodeError::BadLength { expected, got } => {
+                write!(
+                    f,
+                    "unexpected packet length: expected {expected}, got {got}"
+                )
+            }
+            SparsnasDecodeError::BadSerial { expected, got } => write!(
+                f,
+                "serial in packet ({got}) does not match configured serial ({expected})"
+            ),
+            SparsnasDecodeError::BadPacketCount { expected, got } => write!(
+                f,
+                "packet sequence mismatch: expected sequence byte {expected}, got {got}"
+            ),
+            SparsnasDecodeError::UnknownTransmitter { serial: None } => {
+                f.write_str("packet is not from any configured transmitter")
+            }
+            SparsnasDecodeError::UnknownTransmitter {
+                serial: Some(serial),
+            } => write!(
+                f,
+                "packet is not from any configured transmitter, serial in packet ({serial})"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SparsnasDecodeError {}
+
+/// Error decoding a packet from a hex string, see
+/// [SparsnasDecoder::decode_hex].
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DecodeHexError {
+    /// The hex string contains something else than hex digit pairs,
+    /// whitespace and colons.
+    InvalidHex,
+    /// The hex string does not contain 20 bytes.
+    WrongLength {
+        /// Number of bytes in the hex string.
+        got: usize,
+    },
+    /// The bytes are not a valid packet.
+    DecodeError(SparsnasDecodeError),
+}
+
+impl From<SparsnasDecodeError> for DecodeHexError {
+    fn from(e: SparsnasDecodeError) -> Self {
+        DecodeHexError::DecodeError(e)
+    }
+}
+
+impl core::fmt::Display for DecodeHexError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeHexError::InvalidHex => f.write_str("malformed hex string"),
+            DecodeHexError::WrongLength { got } => {
+                write!(f, "expected 20 bytes in hex string, got {got}")
+            }
+            DecodeHexError::DecodeError(e) => write!(f, "bad packet in hex string: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeHexError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeHexError::DecodeError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Error creating a [SparsnasDecoder].
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum SparsnasDecoderError {
+    /// The serial number string contains something else than digits
+    /// and dashes.
+    InvalidCharacter(char),
+    /// The serial number string does not have exactly 9 digits.
+    BadDigitCount {
+        /// Number of digits found.
+        got: usize,
+    },
+    /// The serial number does not have exactly 9 digits, i.e is not
+    /// in range 100000000..=999999999.
+    SerialOutOfRange(u32),
+}
+
+impl core::fmt::Display for SparsnasDecoderError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SparsnasDecoderError::InvalidCharacter(c) => {
+                write!(f, "invalid character {c:?} in serial number")
+            }
+            SparsnasDecoderError::BadDigitCount { got } => {
+                write!(f, "expected 9 digits in serial number, got {got}")
+            }
+            SparsnasDecoderError::SerialOutOfRange(serial) => {
+                write!(
+                    f,
+                    "serial number {serial} out of range 100000000..=999999999"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SparsnasDecoderError {}
+
+impl core::fmt::Display for SparsnasPacket {
+    /// One line summary of the packet, e.g
+    /// `seq=36 power=60W interval=59953ms pulses=9 battery=100% status=0x40c1 serial=565321`.
+    ///
+    /// The power is calculated using [DEFAULT_PULSES_PER_KWH], use
+    /// [SparsnasPacket::display_with] for meters with another pulse
+    /// constant. It is left out if there is none, see
+    /// [SparsnasPacket::power]. With the alternate flag (`{:#}`) each
+    /// field is on a line of its own.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.display_with(DEFAULT_PULSES_PER_KWH), f)
+    }
+}
+
+/// Displays a packet with the power calculated using a given pulse
+/// constant, see [SparsnasPacket::display_with].
+#[derive(Debug, Clone, Copy)]
+pub struct SparsnasPacketDisplay<'a> {
+    packet: &'a SparsnasPacket,
+    pulses_per_kwh: u32,
+}
+
+impl core::fmt::Display for SparsnasPacketDisplay<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let pkt = self.packet;
+        let power = pkt.power(self.pulses_per_kwh);
+        let interval = pkt.time_between_pulses as u32 * 1000 / TIMER_HZ;
+
+        if f.alternate() {
+            writeln!(f, "seq:      {}", pkt.packet_seq)?;
+            if let Some(power) = power {
+                writeln!(f, "power:    {power} W")?;
+            }
+            writeln!(f, "interval: {interval} ms")?;
+            writeln!(f, "pulses:   {}", pkt.pulse_count)?;
+            writeln!(f, "battery:  {}%", pkt.battery_percentage)?;
+            writeln!(f, "status:   {:#06x}", pkt.status)?;
+            write!(f, "serial:   {}", pkt.serial)
+        } else {
+            write!(f, "seq={}", pkt.packet_seq)?;
+            if let Some(power) = power {
+                write!(f, " power={power}W")?;
+            }
+            write!(
+                f,
+                " interval={interval}ms pulses={} battery={}% status={:#06x} serial={}",
+                pkt.pulse_count, pkt.battery_percentage, pkt.status, pkt.serial
+            )
+        }
+    }
 }
 
 impl SparsnasPacket {
+    /// The decrypted 18 byte packet, without CRC. Byte 0 is the
+    /// length, byte 2 the low 7 bits of the sequence number, the
+    /// rest is laid out as in the encrypted packet.
+    pub fn raw_plaintext(&self) -> [u8; 18] {
+        let mut data = [0u8; 18];
+        data[0] = 17;
+        data[1] = self.raw_byte1;
+        data[2] = (self.packet_seq & 0x7f) as u8;
+        data[3..5].copy_from_slice(&self.status.to_be_bytes());
+        data[5..9].copy_from_slice(&self.serial.to_be_bytes());
+        data[9..11].copy_from_slice(&self.packet_seq.to_be_bytes());
+        data[11..13].copy_from_slice(&self.time_between_pulses.to_be_bytes());
+        data[13..17].copy_from_slice(&self.pulse_count.to_be_bytes());
+        data[17] = self.battery_percentage;
+        data
+    }
+
+    /// Display the packet like its [core::fmt::Display]
+    /// implementation, but with the power calculated using
+    /// `pulses_per_kwh` instead of [DEFAULT_PULSES_PER_KWH]. The power
+    /// is left out if there is none, see [SparsnasPacket::power].
+    pub fn display_with(&self, pulses_per_kwh: u32) -> SparsnasPacketDisplay<'_> {
+        SparsnasPacketDisplay {
+            packet: self,
+            pulses_per_kwh,
+        }
+    }
+
+    /// True if this packet was sent after `other`, according to the
+    /// sequence numbers, see [seq_distance].
+    pub fn is_newer_than(&self, other: &SparsnasPacket) -> bool {
+        seq_distance(other.packet_seq, self.packet_seq) > 0
+    }
+
+    /// The status word, with accessors for its parts.
+    pub fn status_flags(&self) -> SparsnasStatus {
+        SparsnasStatus::new(self.status)
+    }
+
+    /// Battery percentage, capped at 100%. The transmitter sometimes
+    /// reports values above 100.
+    pub fn battery_percentage_clamped(&self) -> u8 {
+        self.battery_percentage.min(100)
+    }
+
+    /// Battery state, based on the battery percentage.
+    pub fn battery_state(&self) -> BatteryState {
+        if self.battery_is_critical() {
+            BatteryState::Critical
+        } else if self.battery_is_low() {
+            BatteryState::Low
+        } else {
+            BatteryState::Ok
+        }
+    }
+
+    /// True if the battery is below [DEFAULT_LOW_BATTERY_THRESHOLD],
+    /// 20%.
+    pub fn battery_is_low(&self) -> bool {
+        self.battery_is_low_threshold(DEFAULT_LOW_BATTERY_THRESHOLD)
+    }
+
+    /// True if the battery is below `threshold` percent.
+    pub fn battery_is_low_threshold(&self, threshold: u8) -> bool {
+        self.battery_percentage < threshold
+    }
+
+    /// True if the battery is below [CRITICAL_BATTERY_THRESHOLD], 10%.
+    pub fn battery_is_critical(&self) -> bool {
+        self.battery_is_low_threshold(CRITICAL_BATTERY_THRESHOLD)
+    }
+
+    /// True if the transmitter has not seen any pulse since it was
+    /// powered on, which it reports as a time between pulses of 0xffff
+    /// and a pulse count of zero.
+    pub fn is_standby(&self) -> bool {
+        !self.has_recent_pulse() && self.pulse_count == 0
+    }
+
+    /// True if there is no flow, i.e the transmitter has seen at least
+    /// one pulse but the time between pulses is saturated at 0xffff,
+    /// about 64 seconds at [TIMER_HZ]. [SparsnasPacket::power] reports
+    /// this as zero rather than the 56 W the value would give at 1000
+    /// pulses per kWh.
+    pub fn is_idle(&self) -> bool {
+        !self.has_recent_pulse() && self.pulse_count != 0
+    }
+
+    /// True if [SparsnasPacket::time_between_pulses] is a real
+    /// interval, i.e not [TIME_BETWEEN_PULSES_SATURATED]. Otherwise the
+    /// transmitter is either in standby or idle, see
+    /// [SparsnasPacket::is_standby] and [SparsnasPacket::is_idle], for
+    /// which the power is `None` and zero.
+    pub fn has_recent_pulse(&self) -> bool {
+        self.time_between_pulses != TIME_BETWEEN_PULSES_SATURATED
+    }
+
     /// Calculate and return power usage as reported in the packet.
     ///
-    /// `pulses_per_khw`: The number of pulses the meter gives per kWh. (usually 1000)
-    pub fn power(&self, pulses_per_khw: u32) -> u32 {
-        3686400000u32 / (pulses_per_khw * self.time_between_pulses as u32)
+    /// `pulses_per_kwh`: The number of pulses the meter gives per kWh. (usually 1000)
+    ///
+    /// Returns `None` if `time_between_pulses` or `pulses_per_kwh` is
+    /// zero, or if the transmitter is in standby, see
+    /// [SparsnasPacket::is_standby]. Returns `Some(0)` if there is no
+    /// flow, see [SparsnasPacket::is_idle].
+    pub fn power(&self, pulses_per_kwh: u32) -> Option<u32> {
+        self.power_with(pulses_per_kwh, Rounding::Floor)
+    }
+
+    /// Calculate power usage like [SparsnasPacket::power], but rounded
+    /// to whole watts as specified by `rounding` instead of always
+    /// down.
+    pub fn power_with(&self, pulses_per_kwh: u32, rounding: Rounding) -> Option<u32> {
+        if pulses_per_kwh == 0 || self.time_between_pulses == 0 || self.is_standby() {
+            return None;
+        }
+        if self.is_idle() {
+            return Some(0);
+        }
+        let n = 3686400000u64;
+        let d = pulses_per_kwh as u64 * self.time_between_pulses as u64;
+        // the result is at most 3686400000, which fits in u32
+        Some(match rounding {
+            Rounding::Floor => n / d,
+            Rounding::Nearest => (n + d / 2) / d,
+            Rounding::Ceil => n.div_ceil(d),
+        } as u32)
+    }
+
+    /// Calculate power usage like [SparsnasPacket::power], but without
+    /// truncating to whole watts.
+    ///
+    /// [SparsnasPacket::power] is this value rounded down, which for
+    /// long times between pulses, i.e low power, loses a large part
+    /// of the value: a time between pulses of 3600 at 1000 pulses per
+    /// kWh is 1024.0 W, while 3601 gives 1023.7 W but 1023 W from the
+    /// integer version.
+    ///
+    /// Returns `None` and zero in the same cases as
+    /// [SparsnasPacket::power].
+    pub fn power_f64(&self, pulses_per_kwh: u32) -> Option<f64> {
+        if pulses_per_kwh == 0 || self.time_between_pulses == 0 || self.is_standby() {
+            return None;
+        }
+        if self.is_idle() {
+            return Some(0.0);
+        }
+        Some(3686400000.0 / (pulses_per_kwh as f64 * self.time_between_pulses as f64))
+    }
+
+    /// Calculate power usage like [SparsnasPacket::power], but in
+    /// milliwatts, for when whole watts are too coarse but floating
+    /// point is not wanted. Rounded down like [SparsnasPacket::power].
+    ///
+    /// Returns `None` and zero in the same cases as
+    /// [SparsnasPacket::power].
+    pub fn power_milliwatts(&self, pulses_per_kwh: u32) -> Option<u64> {
+        if pulses_per_kwh == 0 || self.time_between_pulses == 0 || self.is_standby() {
+            return None;
+        }
+        if self.is_idle() {
+            return Some(0);
+        }
+        Some(3686400000000u64 / (pulses_per_kwh as u64 * self.time_between_pulses as u64))
+    }
+
+    /// Time between pulses in seconds, i.e
+    /// [SparsnasPacket::time_between_pulses] divided by [TIMER_HZ].
+    pub fn time_between_pulses_secs(&self) -> f64 {
+        self.time_between_pulses as f64 / TIMER_HZ as f64
+    }
+
+    /// Calculate total energy in kWh from the pulse count.
+    ///
+    /// This is the energy measured since the transmitter powered
+    /// on. The pulse count restarts from zero when the transmitter is
+    /// restarted, e.g. when changing batteries.
+    ///
+    /// Returns `None` if `pulses_per_kwh` is zero.
+    pub fn energy_kwh(&self, pulses_per_kwh: u32) -> Option<f64> {
+        if pulses_per_kwh == 0 {
+            return None;
+        }
+        Some(self.pulse_count as f64 / pulses_per_kwh as f64)
+    }
+
+    /// Like [SparsnasPacket::energy_kwh], but using integer division,
+    /// i.e rounded down to whole kWh.
+    pub fn energy_kwh_u32(&self, pulses_per_kwh: u32) -> Option<u32> {
+        self.pulse_count.checked_div(pulses_per_kwh)
+    }
+
+    /// Like [SparsnasPacket::energy_kwh], but in whole Wh, calculated
+    /// in u64 so that no precision is lost for any pulse count.
+    ///
+    /// Returns `None` if `pulses_per_kwh` is zero.
+    pub fn energy_wh(&self, pulses_per_kwh: u32) -> Option<u64> {
+        (self.pulse_count as u64 * 1000).checked_div(pulses_per_kwh as u64)
+    }
+
+    /// Number of pulses from an `earlier` packet to this one, without
+    /// any checks.
+    ///
+    /// If the pulse count in this packet is lower, the counter is
+    /// assumed to have wrapped around once. If it wrapped more than
+    /// once the result is off by a multiple of 2^32, which can not be
+    /// detected from the two packets. Note that the pulse count also
+    /// restarts from zero when the transmitter is restarted, which
+    /// this can not tell apart either, see
+    /// [SparsnasSession::detect_reset]. [SparsnasPacket::pulse_delta]
+    /// takes a restart into account.
+    pub fn delta_pulse_count(&self, earlier: &SparsnasPacket) -> u64 {
+        if self.pulse_count >= earlier.pulse_count {
+            (self.pulse_count - earlier.pulse_count) as u64
+        } else {
+            (u32::MAX as u64 + 1) - earlier.pulse_count as u64 + self.pulse_count as u64
+        }
+    }
+
+    /// Calculate the energy in kWh consumed between an earlier packet
+    /// and this one, from the difference in pulse count, see
+    /// [SparsnasPacket::delta_pulse_count].
+    ///
+    /// Returns `None` if `pulses_per_kwh` is zero.
+    pub fn consumption_since(&self, earlier: &SparsnasPacket, pulses_per_kwh: u32) -> Option<f64> {
+        if pulses_per_kwh == 0 {
+            return None;
+        }
+        let pulses = self.delta_pulse_count(earlier);
+        Some(pulses as f64 / pulses_per_kwh as f64)
+    }
+
+    /// Number of pulses from an `earlier` packet from the same
+    /// transmitter to this one.
+    ///
+    /// Returns `None` if the serial numbers differ, or if the pulse
+    /// count seems to have gone backwards because the transmitter
+    /// restarted. A lower pulse count is taken for the counter
+    /// wrapping around if that gives less than 2^31 pulses, like for
+    /// sequence numbers in [seq_distance], otherwise for a restart.
+    /// [SparsnasPacket::delta_pulse_count], which takes its arguments
+    /// in the same order, checks nothing and always assumes a wrap.
+    pub fn pulse_delta(&self, earlier: &SparsnasPacket) -> Option<u32> {
+        if self.serial != earlier.serial {
+            return None;
+        }
+        let pulses = self.pulse_count.wrapping_sub(earlier.pulse_count);
+        if pulses > i32::MAX as u32 {
+            return None;
+        }
+        Some(pulses)
+    }
+
+    /// Energy in whole Wh consumed between an `earlier` packet from
+    /// the same transmitter and this one, see
+    /// [SparsnasPacket::pulse_delta].
+    ///
+    /// Returns `None` if `pulses_per_kwh` is zero, or in the same cases
+    /// as [SparsnasPacket::pulse_delta].
+    pub fn energy_delta_wh(&self, earlier: &SparsnasPacket, pulses_per_kwh: u32) -> Option<u64> {
+        let pulses = self.pulse_delta(earlier)?;
+        (pulses as u64 * 1000).checked_div(pulses_per_kwh as u64)
+    }
+}
+
+/// Check the CRC at the end of a packet.
+pub(crate) fn check_crc(data: &[u8; 20]) -> Result<(), SparsnasDecodeError> {
+    let expected = ikeacrc::crc(&data[0..18]);
+
+    let got = u16::from_be_bytes([data[18], data[19]]);
+    if got != expected {
+        return Err(SparsnasDecodeError::BadCRC { expected, got });
+    }
+
+    Ok(())
+}
+
+impl core::str::FromStr for SparsnasDecoder {
+    type Err = SparsnasDecoderError;
+
+    /// Same as [SparsnasDecoder::from_serial_str].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_serial_str(s)
+    }
+}
+
+impl TryFrom<&str> for SparsnasDecoder {
+    type Error = SparsnasDecoderError;
+
+    /// Same as [SparsnasDecoder::from_serial_str].
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::from_serial_str(s)
     }
 }
 
@@ -54,17 +691,184 @@ impl SparsnasDecoder {
     ///
     /// The serial number is on a label behind the batteries, 9 digits
     /// (nnn-nnn-nnn)
-    pub fn new(serial: u32) -> Self {
-        let xorbase = (serial + 0x8AEF9335).to_le_bytes();
+    ///
+    /// The key is derived from the full serial number. The packets
+    /// only carry the last 6 digits of it.
+    ///
+    /// Panics if the serial number is out of range, see
+    /// [SparsnasDecoder::try_new].
+    pub const fn new(serial: u32) -> Self {
+        match Self::try_new(serial) {
+            Ok(d) => d,
+            Err(_) => panic!("Sparsnas serial number must have 9 digits"),
+        }
+    }
+
+    /// Create a new decoder for specified serial number, checking
+    /// that it has 9 digits, i.e is in range 100000000..=999999999.
+    ///
+    /// A serial number with a digit missing derives a key that does
+    /// not match the transmitter, so all packets would be rejected.
+    pub const fn try_new(serial: u32) -> Result<Self, SparsnasDecoderError> {
+        if serial < 100_000_000 || serial > 999_999_999 {
+            return Err(SparsnasDecoderError::SerialOutOfRange(serial));
+        }
+        Ok(Self::with_key(serial, Self::key_for_serial(serial)))
+    }
+
+    /// Derive the key used by the transmitter with the specified
+    /// serial number.
+    ///
+    /// The serial number plus [KEY_DERIVATION_MAGIC] (wrapping), as
+    /// little endian bytes `b`, gives the key `[0x47, b[2], b[3],
+    /// b[0], b[1]]`.
+    pub const fn key_for_serial(serial: u32) -> [u8; 5] {
+        let xorbase = serial.wrapping_add(KEY_DERIVATION_MAGIC).to_le_bytes();
+        [0x47, xorbase[2], xorbase[3], xorbase[0], xorbase[1]]
+    }
+
+    /// Create a new decoder from a serial number string as printed on
+    /// the label, e.g "400-565-321". The groups may also be separated
+    /// by spaces or not at all, but there must be exactly 9 digits.
+    pub fn from_serial_str(s: &str) -> Result<Self, SparsnasDecoderError> {
+        let mut serial = 0u32;
+        let mut digits = 0;
+        for c in s.trim().chars() {
+            if c == '-' || c == ' ' {
+                continue;
+            }
+            let d = c
+                .to_digit(10)
+                .ok_or(SparsnasDecoderError::InvalidCharacter(c))?;
+            digits += 1;
+            if digits <= 9 {
+                serial = serial * 10 + d;
+            }
+        }
+        if digits != 9 {
+            return Err(SparsnasDecoderError::BadDigitCount { got: digits });
+        }
+        Self::try_new(serial)
+    }
+
+    /// Create a new decoder from a known key and the last 6 digits of
+    /// the serial number, for when the full serial number is not
+    /// known.
+    ///
+    /// `SparsnasDecoder::from_key(d.key(), d.serial() % 1_000_000)`
+    /// decodes the same packets as `d`.
+    pub const fn from_key(key: [u8; 5], serial_suffix: u32) -> Self {
+        Self::with_key(serial_suffix, key)
+    }
+
+    /// Create a new decoder using a precomputed key instead of
+    /// deriving it from the serial number. The serial number is only
+    /// used to check the serial in decoded packets.
+    pub const fn with_key(serial: u32, key: [u8; 5]) -> Self {
         SparsnasDecoder {
             serial,
-            key: [0x47, xorbase[2], xorbase[3], xorbase[0], xorbase[1]],
+            key,
+            pulses_per_kwh: DEFAULT_PULSES_PER_KWH,
+            check_serial: true,
+            check_seq: true,
+        }
+    }
+
+    /// Set whether decoding checks that the serial number in packets
+    /// matches the one of the decoder. Enabled by default.
+    ///
+    /// With the check disabled packets from other transmitters are
+    /// decoded with the wrong key, giving garbage values. This is
+    /// useful when looking for the right key.
+    pub const fn with_serial_check(mut self, check_serial: bool) -> Self {
+        self.check_serial = check_serial;
+        self
+    }
+
+    /// Set whether decoding checks that the unencrypted sequence byte
+    /// matches the decrypted sequence number. Enabled by default.
+    pub const fn with_seq_check(mut self, check_seq: bool) -> Self {
+        self.check_seq = check_seq;
+        self
+    }
+
+    /// Set the number of pulses per kWh the meter gives, used by
+    /// [SparsnasDecoder::power] and [SparsnasDecoder::energy_kwh].
+    /// Defaults to [DEFAULT_PULSES_PER_KWH].
+    pub const fn with_pulses_per_kwh(mut self, pulses_per_kwh: u32) -> Self {
+        self.pulses_per_kwh = pulses_per_kwh;
+        self
+    }
+
+    /// The number of pulses per kWh the meter gives.
+    pub const fn pulses_per_kwh(&self) -> u32 {
+        self.pulses_per_kwh
+    }
+
+    /// Calculate the power usage of a packet, like
+    /// [SparsnasPacket::power] with the pulses per kWh of the decoder.
+    pub fn power(&self, pkt: &SparsnasPacket) -> Option<u32> {
+        pkt.power(self.pulses_per_kwh)
+    }
+
+    /// Calculate the total energy of a packet, like
+    /// [SparsnasPacket::energy_kwh] with the pulses per kWh of the
+    /// decoder.
+    pub fn energy_kwh(&self, pkt: &SparsnasPacket) -> Option<f64> {
+        pkt.energy_kwh(self.pulses_per_kwh)
+    }
+
+    /// The serial number this decoder was created for. Only the last
+    /// 6 digits if created using [SparsnasDecoder::from_key].
+    pub const fn serial(&self) -> u32 {
+        self.serial
+    }
+
+    /// The key used to decrypt packets.
+    pub const fn key(&self) -> [u8; 5] {
+        self.key
+    }
+
+    /// Encrypt or decrypt (XOR with the key) `data`, which starts at
+    /// `payload_offset` in the 18 byte packet (length byte at offset
+    /// 0). Only bytes from offset 3 and on are encrypted, bytes
+    /// before that are left unchanged.
+    pub fn xor_crypt(&self, payload_offset: usize, data: &mut [u8]) {
+        for (offset, b) in (payload_offset..).zip(data) {
+            if offset >= 3 {
+                *b ^= self.key[(offset - 3) % 5];
+            }
         }
     }
 
     /// Decode a packet without CRC and length.
     pub fn decode_nocrclen(&self, data: &[u8; 17]) -> Result<SparsnasPacket, SparsnasDecodeError> {
+        let pkt = self.decrypt_nocrclen(data);
+
+        let expected = (pkt.packet_seq & 0x7f) as u8;
+        if self.check_seq && expected != data[1] {
+            return Err(SparsnasDecodeError::BadPacketCount {
+                expected,
+                got: data[1],
+            });
+        }
+
+        let expected = self.serial % 1_000_000;
+        if self.check_serial && pkt.serial != expected {
+            return Err(SparsnasDecodeError::BadSerial {
+                expected,
+                got: pkt.serial,
+            });
+        }
+
+        Ok(pkt)
+    }
+
+    /// Decrypt the fields of a packet without CRC and length, without
+    /// checking anything.
+    pub(crate) fn decrypt_nocrclen(&self, data: &[u8; 17]) -> SparsnasPacket {
         #[rustfmt::skip] // rustfmt makes _some_ of these single line
+        #[allow(clippy::zero_prefixed_literal)] // keep indices aligned
         let pkt = SparsnasPacket {
             status: u16::from_be_bytes([
 		data[02] ^ self.key[0],
@@ -93,44 +897,218 @@ impl SparsnasDecoder {
             battery_percentage: u8::from_be_bytes([
 		data[16] ^ self.key[4],
 	    ]),
+            raw_byte1: data[00],
         };
-
-        if (pkt.packet_seq & 0x7f) as u8 != data[1] {
-            return Err(SparsnasDecodeError::BadPacketCount);
-        }
-
-        if pkt.serial != self.serial % 1_000_000 {
-            return Err(SparsnasDecodeError::BadSerial);
-        }
-
-        Ok(pkt)
+        pkt
     }
 
-    /// Decode a packet without CRC.
+    /// Decode a packet without CRC. A length byte other than 17 is
+    /// reported as [SparsnasDecodeError::BadLength] with `expected` 17.
     pub fn decode_nocrc(&self, data: &[u8; 18]) -> Result<SparsnasPacket, SparsnasDecodeError> {
         if data[0] != 17 {
-            return Err(SparsnasDecodeError::BadLength);
+            return Err(SparsnasDecodeError::BadLength {
+                expected: 17,
+                got: data[0] as usize,
+            });
         }
         self.decode_nocrclen(data[1..18].try_into().unwrap())
     }
 
+    /// Encode a packet without CRC and length. Inverse of [SparsnasDecoder::decode_nocrclen].
+    pub fn encode_nocrclen(&self, pkt: &SparsnasPacket) -> [u8; 17] {
+        let status = pkt.status.to_be_bytes();
+        let serial = pkt.serial.to_be_bytes();
+        let packet_seq = pkt.packet_seq.to_be_bytes();
+        let time_between_pulses = pkt.time_between_pulses.to_be_bytes();
+        let pulse_count = pkt.pulse_count.to_be_bytes();
+
+        [
+            pkt.raw_byte1,
+            (pkt.packet_seq & 0x7f) as u8,
+            status[0] ^ self.key[0],
+            status[1] ^ self.key[1],
+            serial[0] ^ self.key[2],
+            serial[1] ^ self.key[3],
+            serial[2] ^ self.key[4],
+            serial[3] ^ self.key[0],
+            packet_seq[0] ^ self.key[1],
+            packet_seq[1] ^ self.key[2],
+            time_between_pulses[0] ^ self.key[3],
+            time_between_pulses[1] ^ self.key[4],
+            pulse_count[0] ^ self.key[0],
+            pulse_count[1] ^ self.key[1],
+            pulse_count[2] ^ self.key[2],
+            pulse_count[3] ^ self.key[3],
+            pkt.battery_percentage ^ self.key[4],
+        ]
+    }
+
+    /// Encode a packet without CRC. Inverse of [SparsnasDecoder::decode_nocrc].
+    pub fn encode_nocrc(&self, pkt: &SparsnasPacket) -> [u8; 18] {
+        let mut data = [0u8; 18];
+        data[0] = 17;
+        data[1..18].copy_from_slice(&self.encode_nocrclen(pkt));
+        data
+    }
+
+    /// Encode a packet, including length field and CRC. Inverse of [SparsnasDecoder::decode].
+    pub fn encode(&self, pkt: &SparsnasPacket) -> [u8; 20] {
+        let mut data = [0u8; 20];
+        data[0..18].copy_from_slice(&self.encode_nocrc(pkt));
+        let crc = ikeacrc::crc(&data[0..18]);
+        data[18..20].copy_from_slice(&crc.to_be_bytes());
+        data
+    }
+
     /// Decode a packet. Expecting that the buffer contains a length field in the beginning and CRC at the end.
     pub fn decode(&self, data: &[u8; 20]) -> Result<SparsnasPacket, SparsnasDecodeError> {
-        let crc = ikeacrc::crc(&data[0..18]);
+        check_crc(data)?;
+        self.decode_nocrc(data[0..18].try_into().unwrap())
+    }
 
-        if u16::from_be_bytes([data[18], data[19]]) != crc {
-            return Err(SparsnasDecodeError::BadCRC);
+    /// Decode a packet like [SparsnasDecoder::decode], but accept
+    /// packets with any serial number. Returns the packet together
+    /// with whether its serial number matches the one of the
+    /// decoder.
+    ///
+    /// The CRC and the sequence byte are still checked. The sequence
+    /// byte check only passes for packets decrypted with the right
+    /// key, or from a transmitter with a similar key, so this can be
+    /// used to find transmitters near the one the decoder is for.
+    pub fn decode_any(
+        &self,
+        data: &[u8; 20],
+    ) -> Result<(SparsnasPacket, bool), SparsnasDecodeError> {
+        let pkt = self.with_serial_check(false).decode(data)?;
+        let matched = pkt.serial == self.serial % 1_000_000;
+        Ok((pkt, matched))
+    }
+
+    /// Decode a packet like [SparsnasDecoder::decode], and keep
+    /// `meta` together with the decoded packet.
+    pub fn decode_with_meta<M>(
+        &self,
+        data: &[u8; 20],
+        meta: M,
+    ) -> Result<DecodedFrame<M>, SparsnasDecodeError> {
+        let packet = self.decode(data)?;
+        Ok(DecodedFrame { packet, meta })
+    }
+
+    /// Decode a packet without the length byte, as received by radios
+    /// in fixed length mode. The length byte is still included in the
+    /// CRC, so it is assumed to be 17 when checking it.
+    pub fn decode_fixed(&self, data: &[u8; 19]) -> Result<SparsnasPacket, SparsnasDecodeError> {
+        let mut full = [17u8; 20];
+        full[1..20].copy_from_slice(data);
+        self.decode(&full)
+    }
+
+    /// Decode a packet from a slice. Same as [SparsnasDecoder::decode]
+    /// but the length of the slice is checked at runtime. The slice
+    /// must be exactly 20 bytes, trailing data is not accepted. A slice
+    /// of another size is reported as [SparsnasDecodeError::BadLength]
+    /// with `expected` 20.
+    pub fn decode_slice(&self, data: &[u8]) -> Result<SparsnasPacket, SparsnasDecodeError> {
+        let data = data
+            .try_into()
+            .map_err(|_| SparsnasDecodeError::BadLength {
+                expected: 20,
+                got: data.len(),
+            })?;
+        self.decode(data)
+    }
+
+    /// Decode a packet from a hex string, e.g "11 49 24 ...", as
+    /// printed by e.g rtl_433. Whitespace and colons between the
+    /// digits are ignored.
+    pub fn decode_hex(&self, hex: &str) -> Result<SparsnasPacket, DecodeHexError> {
+        let mut data = [0u8; 20];
+        let mut nibbles = 0;
+        for c in hex.chars().filter(|c| !c.is_whitespace() && *c != ':') {
+            let v = c.to_digit(16).ok_or(DecodeHexError::InvalidHex)?;
+            if let Some(b) = data.get_mut(nibbles / 2) {
+                *b = (*b << 4) | v as u8;
+            }
+            nibbles += 1;
+        }
+        if nibbles % 2 != 0 {
+            return Err(DecodeHexError::InvalidHex);
         }
+        if nibbles != 40 {
+            return Err(DecodeHexError::WrongLength { got: nibbles / 2 });
+        }
+        Ok(self.decode(&data)?)
+    }
 
-        self.decode_nocrc(data[0..18].try_into().unwrap())
+    /// Iterate over the packets in a stream of bytes. Bytes which are
+    /// not part of a valid packet are skipped.
+    pub fn packets<'a>(&'a self, data: &'a [u8]) -> SparsnasPackets<'a> {
+        SparsnasPackets::new(self, data)
+    }
+
+    /// Find and decode all packets in a stream of bytes. Same as
+    /// collecting [SparsnasDecoder::packets].
+    #[cfg(feature = "std")]
+    pub fn decode_stream(&self, data: &[u8]) -> Vec<SparsnasPacket> {
+        self.packets(data).collect()
+    }
+
+    /// Find and decode all packets in a buffer, together with the
+    /// offset in the buffer where each packet starts. Bytes which are
+    /// not part of a valid packet are skipped, a byte that looks like
+    /// the start of a packet but is not does not cause a packet
+    /// starting right after it to be missed.
+    #[cfg(feature = "std")]
+    pub fn decode_all(&self, buf: &[u8]) -> Vec<(usize, SparsnasPacket)> {
+        let mut packets = self.packets(buf);
+        std::iter::from_fn(|| packets.next_with_offset()).collect()
+    }
+
+    /// Decode a packet without CRC from a slice. Same as
+    /// [SparsnasDecoder::decode_nocrc] but the length of the slice is
+    /// checked at runtime. The slice must be exactly 18 bytes. A slice
+    /// of another size is reported as [SparsnasDecodeError::BadLength]
+    /// with `expected` 18.
+    pub fn decode_nocrc_slice(&self, data: &[u8]) -> Result<SparsnasPacket, SparsnasDecodeError> {
+        let data = data
+            .try_into()
+            .map_err(|_| SparsnasDecodeError::BadLength {
+                expected: 18,
+                got: data.len(),
+            })?;
+        self.decode_nocrc(data)
     }
 }
 
 #[cfg(test)]
 mod tests {
 
+    use crate::testdata::*;
     use crate::*;
 
+    const ERRORS: [SparsnasDecodeError; 5] = [
+        SparsnasDecodeError::BadCRC {
+            expected: 0x6957,
+            got: 0xffff,
+        },
+        SparsnasDecodeError::BadLength {
+            expected: 20,
+            got: 19,
+        },
+        SparsnasDecodeError::BadSerial {
+            expected: 547040,
+            got: 565321,
+        },
+        SparsnasDecodeError::BadPacketCount {
+            expected: 43,
+            got: 44,
+        },
+        SparsnasDecodeError::UnknownTransmitter {
+            serial: Some(565321),
+        },
+    ];
+
     #[test]
     fn kodarn() {
         // from https://github.com/kodarn/Sparsnas
@@ -151,6 +1129,7 @@ mod tests {
             battery_percentage: 100,
             status: 16577,
             serial: 565321,
+            raw_byte1: 0x49,
         };
 
         assert_eq!(pkt, expected);
@@ -175,23 +1154,1273 @@ mod tests {
             battery_percentage: 100,
             status: 16577,
             serial: 547040,
+            raw_byte1: 0xe0,
         };
 
         assert_eq!(pkt, expected);
-        assert_eq!(pkt.power(1000), 1845);
+        assert_eq!(pkt.power(1000).unwrap(), 1845);
     }
 
     #[test]
-    fn bad_crc() {
-        let testdata = [
-            0x11, 0xe0, 0x2b, 0x07, 0x0e, 0xa2, 0x1d, 0x28, 0xa7, 0x80, 0x09, 0x12, 0xbe, 0x47,
-            0x8a, 0x20, 0x5b, 0x14, 0xff, 0xff, // last two u8 crc changed
-        ];
+    fn raw_plaintext() {
+        let pkt = SparsnasDecoder::new(KODARN_SERIAL).decode(&KODARN).unwrap();
 
-        let d = SparsnasDecoder::new(400_547_040);
+        assert_eq!(pkt.raw_byte1, 0x49);
+        assert_eq!(pkt.raw_plaintext(), KODARN_PLAINTEXT);
 
-        let res = d.decode(&testdata);
+        let pkt = SparsnasDecoder::new(REAL_SERIAL).decode(&REAL).unwrap();
+
+        assert_eq!(pkt.raw_byte1, 0xe0);
+        assert_eq!(pkt.raw_plaintext(), REAL_PLAINTEXT);
+    }
+
+    #[test]
+    fn battery() {
+        let mut pkt = REAL_PACKET;
+
+        assert_eq!(pkt.battery_state(), BatteryState::Ok);
+        assert!(!pkt.battery_is_low());
+        assert_eq!(pkt.battery_percentage_clamped(), 100);
+
+        pkt.battery_percentage = 120;
+        assert_eq!(pkt.battery_state(), BatteryState::Ok);
+        assert_eq!(pkt.battery_percentage_clamped(), 100);
+
+        pkt.battery_percentage = 20;
+        assert!(!pkt.battery_is_low());
+
+        pkt.battery_percentage = 19;
+        assert_eq!(pkt.battery_state(), BatteryState::Low);
+        assert!(pkt.battery_is_low());
+        assert!(!pkt.battery_is_critical());
+
+        pkt.battery_percentage = 10;
+        assert!(!pkt.battery_is_critical());
+
+        pkt.battery_percentage = 9;
+        assert_eq!(pkt.battery_state(), BatteryState::Critical);
+        assert!(pkt.battery_is_low());
+        assert!(pkt.battery_is_critical());
+
+        pkt.battery_percentage = 0;
+        assert_eq!(pkt.battery_state(), BatteryState::Critical);
+    }
+
+    #[test]
+    fn battery_is_low_threshold() {
+        let mut pkt = SparsnasPacket::builder().battery_percentage(30).build();
+
+        assert!(pkt.battery_is_low_threshold(31));
+        assert!(!pkt.battery_is_low_threshold(30));
+        assert!(!pkt.battery_is_low_threshold(0));
+
+        pkt.battery_percentage = 0;
+        assert!(!pkt.battery_is_low_threshold(0));
+        assert!(pkt.battery_is_low_threshold(1));
+    }
+
+    #[test]
+    fn status_flags() {
+        let pkt = REAL_PACKET;
+
+        assert_eq!(pkt.status_flags().raw(), 16577);
+        assert_eq!(pkt.status_flags().high(), 0x40);
+        assert!(pkt.status_flags().contains(0x40c1));
+    }
+
+    #[test]
+    fn power_zero() {
+        let mut pkt = REAL_PACKET;
+
+        assert_eq!(pkt.power(1000), Some(1845));
+        assert_eq!(pkt.power(0), None);
+
+        pkt.time_between_pulses = 0;
+        assert_eq!(pkt.power(1000), None);
+    }
+
+    #[test]
+    fn power_high_pulse_constant() {
+        let mut pkt = KODARN_PACKET;
+
+        // 10000 * 61392 overflows u32
+        assert_eq!(pkt.power(10000), Some(6));
+        assert_eq!(pkt.power(100_000), Some(0));
+        assert_eq!(pkt.power(u32::MAX), Some(0));
+
+        // 100000 * 60000 overflows u32 too
+        pkt.time_between_pulses = 60_000;
+        assert_eq!(pkt.power(100_000), Some(0));
+        assert_eq!(pkt.power_milliwatts(100_000), Some(614));
+        assert!((pkt.power_f64(100_000).unwrap() - 0.6144).abs() < 1e-9);
+
+        pkt.time_between_pulses = 1;
+        assert_eq!(pkt.power(1), Some(3686400000));
+        assert_eq!(pkt.power(100_000), Some(36864));
+    }
+
+    #[test]
+    fn zero_values() {
+        let zero = SparsnasPacket::builder()
+            .time_between_pulses(0)
+            .battery_percentage(0)
+            .status(0)
+            .build();
+
+        for ppk in [0, 1000] {
+            assert_eq!(zero.power(ppk), None);
+            assert_eq!(zero.power_f64(ppk), None);
+        }
+        assert_eq!(zero.energy_kwh(0), None);
+        assert_eq!(zero.energy_kwh_u32(0), None);
+        assert_eq!(zero.consumption_since(&zero, 0), None);
+        assert_eq!(zero.time_between_pulses_secs(), 0.0);
+        assert_eq!(
+            zero.display_with(0).to_string(),
+            "seq=0 interval=0ms pulses=0 battery=0% status=0x0000 serial=0"
+        );
+    }
+
+    #[test]
+    fn standby() {
+        let mut pkt = SparsnasPacket::builder()
+            .packet_seq(1)
+            .serial(547040)
+            .build();
+
+        assert!(pkt.is_standby());
+        assert!(!pkt.is_idle());
+        assert_eq!(pkt.power(1000), None);
+        assert_eq!(pkt.power_f64(1000), None);
+
+        pkt.time_between_pulses = 0xfffe;
+        assert!(!pkt.is_standby());
+        assert_eq!(pkt.power(1000), Some(56));
+    }
+
+    #[test]
+    fn power_with() {
+        let mut pkt = SparsnasPacket::builder()
+            .serial(547040)
+            .pulse_count(4555342)
+            .build();
+
+        // 1023.72 W
+        pkt.time_between_pulses = 3601;
+        assert_eq!(pkt.power_with(1000, Rounding::Floor), Some(1023));
+        assert_eq!(pkt.power_with(1000, Rounding::Nearest), Some(1024));
+        assert_eq!(pkt.power_with(1000, Rounding::Ceil), Some(1024));
+        assert_eq!(pkt.power(1000), Some(1023));
+
+        // 60.05 W
+        pkt.time_between_pulses = 61392;
+        assert_eq!(pkt.power_with(1000, Rounding::Floor), Some(60));
+        assert_eq!(pkt.power_with(1000, Rounding::Nearest), Some(60));
+        assert_eq!(pkt.power_with(1000, Rounding::Ceil), Some(61));
+
+        // 1024 W exactly, and halves
+        pkt.time_between_pulses = 3600;
+        for rounding in [Rounding::Floor, Rounding::Nearest, Rounding::Ceil] {
+            assert_eq!(pkt.power_with(1000, rounding), Some(1024));
+        }
+        pkt.time_between_pulses = 2;
+        assert_eq!(pkt.power_with(3686400000 / 5, Rounding::Nearest), Some(3));
+
+        pkt.time_between_pulses = 1;
+        assert_eq!(pkt.power_with(1, Rounding::Ceil), Some(3686400000));
+
+        pkt.time_between_pulses = 0xffff;
+        assert_eq!(pkt.power_with(1000, Rounding::Ceil), Some(0));
+        pkt.time_between_pulses = 0;
+        assert_eq!(pkt.power_with(1000, Rounding::Ceil), None);
+    }
+
+    #[test]
+    fn power_milliwatts() {
+        let mut pkt = REAL_PACKET;
+
+        assert_eq!(pkt.power_milliwatts(1000), Some(1845045));
+
+        for (ppk, shortest, longest) in [
+            (500, 7372800000, 112503),
+            (1000, 3686400000, 56251),
+            (2000, 1843200000, 28125),
+            (10000, 368640000, 5625),
+        ] {
+            pkt.time_between_pulses = 1;
+            assert_eq!(pkt.power_milliwatts(ppk), Some(shortest));
+            assert_eq!(pkt.power(ppk), Some((shortest / 1000) as u32));
+
+            pkt.time_between_pulses = 65534;
+            assert_eq!(pkt.power_milliwatts(ppk), Some(longest));
+            assert_eq!(pkt.power(ppk), Some((longest / 1000) as u32));
+
+            // idle
+            pkt.time_between_pulses = 65535;
+            assert_eq!(pkt.power_milliwatts(ppk), Some(0));
+        }
+
+        // standby
+        pkt.pulse_count = 0;
+        assert_eq!(pkt.power_milliwatts(1000), None);
+
+        pkt.time_between_pulses = 0;
+        assert_eq!(pkt.power_milliwatts(1000), None);
+        pkt.time_between_pulses = 1998;
+        assert_eq!(pkt.power_milliwatts(0), None);
+    }
+
+    #[test]
+    fn has_recent_pulse() {
+        let mut pkt = SparsnasPacket::builder()
+            .serial(547040)
+            .pulse_count(4555342)
+            .build();
+
+        for (time_between_pulses, recent, power) in [
+            (0xffff, false, Some(0)),
+            (0xfffe, true, Some(56)),
+            (1998, true, Some(1845)),
+        ] {
+            pkt.time_between_pulses = time_between_pulses;
+            assert_eq!(pkt.has_recent_pulse(), recent);
+            assert_eq!(pkt.power(1000), power);
+            assert_eq!(pkt.power_f64(1000).map(|p| p as u32), power);
+        }
+
+        pkt.time_between_pulses = TIME_BETWEEN_PULSES_SATURATED;
+        pkt.pulse_count = 0;
+        assert!(!pkt.has_recent_pulse());
+        assert_eq!(pkt.power(1000), None);
+    }
+
+    #[test]
+    fn idle() {
+        let mut pkt = REAL_PACKET;
+        pkt.time_between_pulses = 0xffff;
+
+        assert!(pkt.is_idle());
+        assert!(!pkt.is_standby());
+        assert_eq!(pkt.power(1000), Some(0));
+        assert_eq!(pkt.power_f64(1000), Some(0.0));
+        assert_eq!(pkt.power(0), None);
+
+        pkt.time_between_pulses = 0xfffe;
+        assert!(!pkt.is_idle());
+        assert_eq!(pkt.power(1000), Some(56));
+    }
+
+    #[test]
+    fn power_f64() {
+        let mut pkt = REAL_PACKET;
+
+        assert!((pkt.power_f64(1000).unwrap() - pkt.power(1000).unwrap() as f64).abs() < 0.5);
+        assert_eq!(pkt.power_f64(0), None);
+
+        pkt.time_between_pulses = 61392;
+        assert!((pkt.power_f64(1000).unwrap() - 60.047).abs() < 0.001);
+
+        pkt.time_between_pulses = 0;
+        assert_eq!(pkt.power_f64(1000), None);
+    }
+
+    #[test]
+    fn power_f64_exact() {
+        let mut pkt = REAL_PACKET;
+
+        // 3686400000 / (ppk * tbp), computed by hand
+        for (ppk, tbp, expected) in [
+            (1000, 3600, 1024.0),
+            (1000, 3601, 1023.7156345459595),
+            (1000, 1998, 1845.045045045045),
+            (1000, 61392, 60.046911649726),
+            (500, 3600, 2048.0),
+            (10000, 1, 368640.0),
+            (1000, 65534, 56.251716666158),
+        ] {
+            pkt.time_between_pulses = tbp;
+            let power = pkt.power_f64(ppk).unwrap();
+            assert!(
+                ((power - expected) / expected).abs() < 1e-9,
+                "{ppk} {tbp}: {power} != {expected}"
+            );
+            assert_eq!(pkt.power(ppk), Some(power as u32));
+        }
+    }
+
+    #[test]
+    fn time_between_pulses_secs() {
+        let mut pkt = KODARN_PACKET;
+
+        assert_eq!(pkt.time_between_pulses_secs(), 59.953125);
+
+        // 1 pulse per TIMER_HZ ticks at 1000 pulses/kWh is 3600 W
+        pkt.time_between_pulses = TIMER_HZ as u16;
+        assert_eq!(pkt.time_between_pulses_secs(), 1.0);
+        assert_eq!(pkt.power(1000), Some(3600));
+    }
+
+    #[test]
+    fn energy_kwh() {
+        let pkt = REAL_PACKET;
+
+        assert_eq!(pkt.energy_kwh(1000), Some(4555.342));
+        assert_eq!(pkt.energy_kwh(0), None);
+        assert_eq!(pkt.energy_kwh_u32(1000), Some(4555));
+        assert_eq!(pkt.energy_kwh_u32(0), None);
+        assert_eq!(pkt.energy_wh(1000), Some(4555342));
+        assert_eq!(pkt.energy_wh(800), Some(5694177));
+        assert_eq!(pkt.energy_wh(0), None);
+    }
+
+    #[test]
+    fn energy_wh_large() {
+        let pkt = SparsnasPacket::builder().pulse_count(u32::MAX).build();
+
+        assert_eq!(pkt.energy_wh(1), Some(u32::MAX as u64 * 1000));
+        assert_eq!(pkt.energy_wh(1000), Some(u32::MAX as u64));
+        assert_eq!(pkt.energy_wh(10000), Some(429496729));
+        assert_eq!(pkt.energy_kwh_u32(10000), Some(429496));
+    }
+
+    #[test]
+    fn consumption_since() {
+        let earlier = REAL_PACKET;
+        let mut pkt = earlier;
+        pkt.pulse_count += 1500;
+
+        assert_eq!(pkt.consumption_since(&earlier, 1000), Some(1.5));
+        assert_eq!(pkt.consumption_since(&earlier, 0), None);
+        assert_eq!(earlier.consumption_since(&earlier, 1000), Some(0.0));
+
+        let mut earlier = earlier;
+        earlier.pulse_count = u32::MAX - 499;
+        pkt.pulse_count = 1500;
+        assert_eq!(pkt.consumption_since(&earlier, 1000), Some(2.0));
+    }
+
+    #[test]
+    fn delta_pulse_count() {
+        let earlier = SparsnasPacket::builder().pulse_count(4555342).build();
+        let mut later = earlier;
+
+        assert_eq!(later.delta_pulse_count(&earlier), 0);
+
+        later.pulse_count = 4556842;
+        assert_eq!(later.delta_pulse_count(&earlier), 1500);
+
+        later.pulse_count = u32::MAX;
+        assert_eq!(
+            later.delta_pulse_count(&earlier),
+            (u32::MAX - 4555342) as u64
+        );
+
+        // wrapped once
+        later.pulse_count = 4555341;
+        assert_eq!(later.delta_pulse_count(&earlier), u32::MAX as u64);
+        later.pulse_count = 0;
+        assert_eq!(later.delta_pulse_count(&earlier), (1 << 32) - 4555342);
+
+        let earlier = SparsnasPacket::builder().pulse_count(u32::MAX).build();
+        assert_eq!(later.delta_pulse_count(&earlier), 1);
+    }
+
+    #[test]
+    fn energy_delta_wh() {
+        let earlier = SparsnasPacket::builder()
+            .serial(547040)
+            .pulse_count(4555342)
+            .build();
+        let mut pkt = earlier;
+
+        assert_eq!(pkt.pulse_delta(&earlier), Some(0));
+        assert_eq!(pkt.energy_delta_wh(&earlier, 1000), Some(0));
+
+        pkt.pulse_count += 1500;
+        assert_eq!(pkt.pulse_delta(&earlier), Some(1500));
+        assert_eq!(pkt.energy_delta_wh(&earlier, 1000), Some(1500));
+        assert_eq!(pkt.energy_delta_wh(&earlier, 800), Some(1875));
+        assert_eq!(pkt.energy_delta_wh(&earlier, 0), None);
+
+        // restarted
+        pkt.pulse_count = 10;
+        assert_eq!(pkt.pulse_delta(&earlier), None);
+        assert_eq!(pkt.energy_delta_wh(&earlier, 1000), None);
+        pkt.pulse_count = 4555341;
+        assert_eq!(pkt.pulse_delta(&earlier), None);
+
+        // other transmitter
+        pkt.pulse_count = 4556842;
+        pkt.serial = 565321;
+        assert_eq!(pkt.pulse_delta(&earlier), None);
+        assert_eq!(pkt.energy_delta_wh(&earlier, 1000), None);
+
+        // wrapped
+        let earlier = SparsnasPacket::builder()
+            .serial(547040)
+            .pulse_count(u32::MAX - 499)
+            .build();
+        pkt.serial = 547040;
+        pkt.pulse_count = 1500;
+        assert_eq!(pkt.pulse_delta(&earlier), Some(2000));
+        assert_eq!(pkt.delta_pulse_count(&earlier), 2000);
+        assert_eq!(pkt.energy_delta_wh(&earlier, 1000), Some(2000));
+
+        pkt.pulse_count = earlier.pulse_count.wrapping_add(i32::MAX as u32);
+        assert_eq!(pkt.pulse_delta(&earlier), Some(i32::MAX as u32));
+        pkt.pulse_count = pkt.pulse_count.wrapping_add(1);
+        assert_eq!(pkt.pulse_delta(&earlier), None);
+    }
+
+    #[test]
+    fn packet_hash_copy() {
+        let pkt = REAL_PACKET;
+
+        #[allow(clippy::clone_on_copy)]
+        let clone = pkt.clone();
+        let copy = pkt;
+        let mut seen = std::collections::HashMap::new();
+        seen.insert(pkt, pkt.serial);
+
+        assert_eq!(clone, pkt);
+        assert_eq!(copy, pkt);
+        assert_eq!(seen.get(&copy), Some(&547040));
+    }
+
+    #[test]
+    fn encode_kodarn() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+
+        let pkt = d.decode(&KODARN).unwrap();
+
+        assert_eq!(d.encode(&pkt), KODARN);
+        assert_eq!(d.encode_nocrc(&pkt), KODARN[0..18]);
+    }
+
+    #[test]
+    fn encode_real() {
+        let d = SparsnasDecoder::new(REAL_SERIAL);
+
+        let pkt = d.decode(&REAL).unwrap();
+
+        assert_eq!(d.encode(&pkt), REAL);
+    }
+
+    #[test]
+    fn encode_roundtrip() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+
+        for (packet_seq, pulse_count) in
+            [(0, 0), (0x7f, 1), (0x80, u32::MAX - 1), (0xffff, u32::MAX)]
+        {
+            let pkt = SparsnasPacket::builder()
+                .packet_seq(packet_seq)
+                .time_between_pulses(0xfffe)
+                .pulse_count(pulse_count)
+                .battery_percentage(42)
+                .status(0xffff)
+                .serial(565321)
+                .raw_byte1(0x49)
+                .build();
+
+            assert_eq!(d.decode(&d.encode(&pkt)), Ok(pkt));
+        }
+    }
+
+    #[test]
+    fn decode_any() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+
+        let (pkt, matched) = d.decode_any(&KODARN).unwrap();
+        assert!(matched);
+        assert_eq!(Ok(pkt), d.decode(&KODARN));
+
+        // encrypted with another key, but the sequence byte still
+        // matches when decrypted with this one
+        let other = SparsnasDecoder::new(REAL_SERIAL);
+        assert!(matches!(
+            other.decode(&KODARN),
+            Err(SparsnasDecodeError::BadSerial { .. })
+        ));
+        let (pkt, matched) = other.decode_any(&KODARN).unwrap();
+        assert!(!matched);
+        assert_eq!(pkt.packet_seq, 36);
+        assert_eq!(pkt.serial, 6514505);
+
+        let mut bad = KODARN;
+        bad[19] ^= 1;
+        assert!(matches!(
+            d.decode_any(&bad),
+            Err(SparsnasDecodeError::BadCRC { .. })
+        ));
+    }
+
+    #[test]
+    fn decode_with_meta() {
+        let d = SparsnasDecoder::new(REAL_SERIAL);
+        let rssi: i8 = -72;
+
+        let frame = d.decode_with_meta(&REAL, rssi).unwrap();
+        assert_eq!(frame.meta, -72);
+        assert_eq!(Ok(&frame.packet), d.decode(&REAL).as_ref());
+        assert_eq!(frame.packet.packet_seq, 20395);
+
+        let mut bad = REAL;
+        bad[19] ^= 1;
+        assert!(matches!(
+            d.decode_with_meta(&bad, rssi),
+            Err(SparsnasDecodeError::BadCRC { .. })
+        ));
+    }
+
+    #[test]
+    fn decode_fixed() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+
+        let pkt = d.decode_fixed(KODARN[1..20].try_into().unwrap());
+        assert!(pkt.is_ok());
+        assert_eq!(pkt, d.decode(&KODARN));
+
+        let mut bad: [u8; 19] = KODARN[1..20].try_into().unwrap();
+        bad[18] ^= 1;
+        assert!(matches!(
+            d.decode_fixed(&bad),
+            Err(SparsnasDecodeError::BadCRC { .. })
+        ));
+    }
+
+    #[test]
+    fn decode_slice() {
+        let testdata = [&REAL[..], &[0x00]].concat();
+
+        let d = SparsnasDecoder::new(REAL_SERIAL);
+
+        assert_eq!(d.decode_slice(&testdata[0..20]), d.decode(&REAL));
+        assert_eq!(d.decode_nocrc_slice(&testdata[0..18]), d.decode(&REAL));
+
+        for len in [0, 18, 19, 21] {
+            assert_eq!(
+                d.decode_slice(&testdata[0..len]),
+                Err(SparsnasDecodeError::BadLength {
+                    expected: 20,
+                    got: len
+                })
+            );
+        }
+        for len in [0, 17, 19] {
+            assert_eq!(
+                d.decode_nocrc_slice(&testdata[0..len]),
+                Err(SparsnasDecodeError::BadLength {
+                    expected: 18,
+                    got: len
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn decode_hex() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+
+        let expected = d.decode(&KODARN).unwrap();
+
+        for hex in [
+            "114924070ea276170ecf86916747cfa277d36e2d",
+            "11 49 24 07 0e a2 76 17 0e cf 86 91 67 47 cf a2 77 d3 6e 2d",
+            "11:49:24:07:0E:A2:76:17:0E:CF:86:91:67:47:CF:A2:77:D3:6E:2D\n",
+            "114924070ea276170ecf8691674 7cfa277d36e2d",
+        ] {
+            assert_eq!(d.decode_hex(hex), Ok(expected));
+        }
+
+        assert_eq!(
+            d.decode_hex("114924070ea276170ecf86916747cfa277d36e"),
+            Err(DecodeHexError::WrongLength { got: 19 })
+        );
+        assert_eq!(
+            d.decode_hex("114924070ea276170ecf86916747cfa277d36e2d00"),
+            Err(DecodeHexError::WrongLength { got: 21 })
+        );
+        assert_eq!(
+            d.decode_hex("114924070ea276170ecf86916747cfa277d36e2"),
+            Err(DecodeHexError::InvalidHex)
+        );
+        assert_eq!(
+            d.decode_hex("114924070ea276170ecf86916747cfa277d36e2x"),
+            Err(DecodeHexError::InvalidHex)
+        );
+        assert_eq!(
+            d.decode_hex("114924070ea276170ecf86916747cfa277d36eff"),
+            Err(DecodeHexError::DecodeError(SparsnasDecodeError::BadCRC {
+                expected: 0x6e2d,
+                got: 0x6eff
+            }))
+        );
+    }
+
+    #[test]
+    fn decode_hex_error_display() {
+        assert_eq!(
+            DecodeHexError::InvalidHex.to_string(),
+            "malformed hex string"
+        );
+        assert_eq!(
+            DecodeHexError::WrongLength { got: 19 }.to_string(),
+            "expected 20 bytes in hex string, got 19"
+        );
+        assert_eq!(
+            DecodeHexError::from(SparsnasDecodeError::BadCRC {
+                expected: 0x6957,
+                got: 0xffff
+            })
+            .to_string(),
+            "bad packet in hex string: CRC mismatch: expected 0x6957, got 0xffff"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn decode_stream() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+
+        let first = d.decode(&KODARN).unwrap();
+        let mut second = first;
+        second.packet_seq += 1;
+
+        let mut stream = vec![0x00, 0x11, 0x42];
+        stream.extend_from_slice(&KODARN);
+        stream.extend_from_slice(&[0x11, 0x11, 0xff]);
+        stream.extend_from_slice(&d.encode(&second));
+        stream.extend_from_slice(&KODARN[0..19]);
+
+        assert_eq!(d.decode_stream(&stream), vec![first, second]);
+        assert_eq!(d.decode_stream(&KODARN[0..19]), vec![]);
+        assert_eq!(d.decode_stream(&[]), vec![]);
+    }
+
+    #[test]
+    fn bad_crc() {
+        let testdata = [
+            0x11, 0xe0, 0x2b, 0x07, 0x0e, 0xa2, 0x1d, 0x28, 0xa7, 0x80, 0x09, 0x12, 0xbe, 0x47,
+            0x8a, 0x20, 0x5b, 0x14, 0xff, 0xff, // last two u8 crc changed
+        ];
+
+        let d = SparsnasDecoder::new(400_547_040);
+
+        let res = d.decode(&testdata);
+
+        assert_eq!(
+            res,
+            Err(SparsnasDecodeError::BadCRC {
+                expected: 0x6957,
+                got: 0xffff
+            })
+        );
+    }
+
+    #[test]
+    fn bad_serial() {
+        // right key, but expecting another serial number
+        let d =
+            SparsnasDecoder::with_key(400_547_040, SparsnasDecoder::key_for_serial(400_565_321));
+
+        assert_eq!(
+            d.decode(&KODARN),
+            Err(SparsnasDecodeError::BadSerial {
+                expected: 547040,
+                got: 565321
+            })
+        );
+    }
+
+    #[test]
+    fn bad_packet_count() {
+        let mut testdata = REAL;
+        testdata[2] = 44;
+        let crc = crc(&testdata[0..18]);
+        testdata[18..20].copy_from_slice(&crc.to_be_bytes());
+
+        let d = SparsnasDecoder::new(REAL_SERIAL);
+
+        assert_eq!(
+            d.decode(&testdata),
+            Err(SparsnasDecodeError::BadPacketCount {
+                expected: 43,
+                got: 44
+            })
+        );
+    }
+
+    #[test]
+    fn bad_length_byte() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+
+        let mut testdata = *nocrc(&KODARN);
+        testdata[0] = 18;
+
+        assert_eq!(
+            d.decode_nocrc(&testdata),
+            Err(SparsnasDecodeError::BadLength {
+                expected: 17,
+                got: 18
+            })
+        );
+    }
+
+    #[test]
+    fn decoder_debug() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+
+        assert_eq!(
+            format!("{:?}", d),
+            "SparsnasDecoder { serial: 400565321, key: [REDACTED], pulses_per_kwh: 1000, \
+             check_serial: true, check_seq: true }"
+        );
+        assert_eq!(d.serial(), 400_565_321);
+
+        for dbg in [format!("{:?}", d), format!("{:#?}", d)] {
+            assert!(!dbg.contains(&format!("{:?}", d.key)));
+            for b in d.key {
+                assert!(!dbg.contains(&b.to_string()));
+            }
+        }
+    }
+
+    #[test]
+    fn const_new() {
+        static DECODER: SparsnasDecoder = SparsnasDecoder::new(KODARN_SERIAL);
+        const CONST_DECODER: SparsnasDecoder = SparsnasDecoder::new(KODARN_SERIAL);
+        const KEY: [u8; 5] = CONST_DECODER.key();
+
+        assert_eq!(DECODER.decode(&KODARN).map(|p| p.packet_seq), Ok(36));
+        assert_eq!(CONST_DECODER.decode(&KODARN), DECODER.decode(&KODARN));
+        assert_eq!(KEY, SparsnasDecoder::key_for_serial(400_565_321));
+    }
+
+    #[test]
+    fn try_new() {
+        for serial in [100_000_000, 400_565_321, 999_999_999] {
+            assert_eq!(
+                SparsnasDecoder::try_new(serial).map(|d| d.serial()),
+                Ok(serial)
+            );
+        }
+        // 40_056_532 is 400-565-321 with a digit missing
+        for serial in [0, 1, 40_056_532, 99_999_999, 1_000_000_000, u32::MAX] {
+            assert_eq!(
+                SparsnasDecoder::try_new(serial).map(|d| d.serial()),
+                Err(SparsnasDecoderError::SerialOutOfRange(serial))
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_out_of_range() {
+        SparsnasDecoder::new(1_000_000_000);
+    }
+
+    #[test]
+    fn key_for_serial() {
+        assert_eq!(
+            SparsnasDecoder::key_for_serial(400_565_321),
+            [0x47, 0xcf, 0xa2, 0x7e, 0xb7]
+        );
+        assert_eq!(
+            SparsnasDecoder::new(REAL_SERIAL).key(),
+            SparsnasDecoder::key_for_serial(400_547_040)
+        );
+    }
+
+    #[test]
+    fn accessors() {
+        // key as derived by kodarn for this serial
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        assert_eq!(d.serial(), 400_565_321);
+        assert_eq!(d.key(), [0x47, 0xcf, 0xa2, 0x7e, 0xb7]);
+    }
+
+    #[test]
+    fn disabled_checks() {
+        let kodarn = SparsnasDecoder::new(KODARN_SERIAL);
+        let expected = kodarn.decode(&KODARN).unwrap();
+
+        // right key, but configured for another serial number
+        let d = SparsnasDecoder::with_key(400_547_040, kodarn.key());
+        assert!(matches!(
+            d.decode(&KODARN),
+            Err(SparsnasDecodeError::BadSerial { .. })
+        ));
+        assert_eq!(d.with_serial_check(false).decode(&KODARN), Ok(expected));
+
+        let mut bad_seq = KODARN;
+        bad_seq[2] ^= 1;
+        let crc = crc(&bad_seq[0..18]);
+        bad_seq[18..20].copy_from_slice(&crc.to_be_bytes());
+        assert!(matches!(
+            kodarn.decode(&bad_seq),
+            Err(SparsnasDecodeError::BadPacketCount { .. })
+        ));
+        assert_eq!(kodarn.with_seq_check(false).decode(&bad_seq), Ok(expected));
+
+        // wrong key, both checks needed to get garbage out
+        let other = SparsnasDecoder::new(REAL_SERIAL)
+            .with_serial_check(false)
+            .with_seq_check(false);
+        assert!(other.decode(&bad_seq).is_ok());
+        assert!(matches!(
+            other.decode(&[0u8; 20]),
+            Err(SparsnasDecodeError::BadCRC { .. })
+        ));
+    }
+
+    #[test]
+    fn pulses_per_kwh() {
+        let d = SparsnasDecoder::new(REAL_SERIAL);
+        assert_eq!(d.pulses_per_kwh(), 1000);
+        let pkt = d.decode(&REAL).unwrap();
+        assert_eq!(d.power(&pkt), Some(1845));
+        assert_eq!(d.energy_kwh(&pkt), Some(4555.342));
+
+        let d2000 = d.with_pulses_per_kwh(2000);
+        assert_eq!(d2000.pulses_per_kwh(), 2000);
+        assert_eq!(d2000.decode(&REAL), Ok(pkt));
+        assert_eq!(d2000.power(&pkt), Some(922));
+        assert_eq!(d2000.power(&pkt), pkt.power(2000));
+        assert_eq!(d2000.energy_kwh(&pkt), Some(4555.342 / 2.0));
+    }
+
+    #[test]
+    fn xor_crypt() {
+        let testdata = *nocrc(&KODARN);
+
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        let pkt = d.decode_nocrc(&testdata).unwrap();
+
+        let mut status = [testdata[3], testdata[4]];
+        d.xor_crypt(3, &mut status);
+        assert_eq!(u16::from_be_bytes(status), pkt.status);
+
+        let mut serial = [testdata[5], testdata[6], testdata[7], testdata[8]];
+        d.xor_crypt(5, &mut serial);
+        assert_eq!(u32::from_be_bytes(serial), pkt.serial);
+
+        let mut seq = [testdata[9], testdata[10]];
+        d.xor_crypt(9, &mut seq);
+        assert_eq!(u16::from_be_bytes(seq), pkt.packet_seq);
+
+        let mut time_between_pulses = [testdata[11], testdata[12]];
+        d.xor_crypt(11, &mut time_between_pulses);
+        assert_eq!(
+            u16::from_be_bytes(time_between_pulses),
+            pkt.time_between_pulses
+        );
+
+        let mut pulse_count = [testdata[13], testdata[14], testdata[15], testdata[16]];
+        d.xor_crypt(13, &mut pulse_count);
+        assert_eq!(u32::from_be_bytes(pulse_count), pkt.pulse_count);
+
+        let mut battery = [testdata[17]];
+        d.xor_crypt(17, &mut battery);
+        assert_eq!(battery[0], pkt.battery_percentage);
+
+        let mut whole = testdata;
+        d.xor_crypt(0, &mut whole);
+        assert_eq!(whole, pkt.raw_plaintext());
+    }
+
+    #[test]
+    fn from_key() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        let k = SparsnasDecoder::from_key(d.key(), 565_321);
+
+        assert_eq!(k.serial(), 565_321);
+        assert_eq!(k.decode(&KODARN), d.decode(&KODARN));
+
+        let w = SparsnasDecoder::with_key(400_565_321, d.key());
+        assert_eq!(w.serial(), 400_565_321);
+        assert_eq!(w.decode(&KODARN), d.decode(&KODARN));
+        assert!(w.decode(&KODARN).is_ok());
+    }
+
+    #[test]
+    fn from_serial_str() {
+        for s in ["400-565-321", "400565321", "400 565 321", " 400-565-321\n"] {
+            assert_eq!(
+                SparsnasDecoder::from_serial_str(s).map(|d| d.serial()),
+                Ok(400_565_321)
+            );
+        }
+        assert_eq!(
+            SparsnasDecoder::try_from("400-565-321").map(|d| d.serial()),
+            Ok(400_565_321)
+        );
+        assert_eq!(
+            "012-345-678".parse::<SparsnasDecoder>().map(|d| d.serial()),
+            Err(SparsnasDecoderError::SerialOutOfRange(12_345_678))
+        );
+
+        for (s, err) in [
+            ("abc", SparsnasDecoderError::InvalidCharacter('a')),
+            ("400-565-32a", SparsnasDecoderError::InvalidCharacter('a')),
+            ("", SparsnasDecoderError::BadDigitCount { got: 0 }),
+            ("40-565-321", SparsnasDecoderError::BadDigitCount { got: 8 }),
+            (
+                "4000-565-321",
+                SparsnasDecoderError::BadDigitCount { got: 10 },
+            ),
+            (
+                "99999999999",
+                SparsnasDecoderError::BadDigitCount { got: 11 },
+            ),
+            ("000-000-000", SparsnasDecoderError::SerialOutOfRange(0)),
+        ] {
+            assert_eq!(
+                SparsnasDecoder::from_serial_str(s).map(|d| d.serial()),
+                Err(err)
+            );
+        }
+    }
+
+    #[test]
+    fn decoder_error_display() {
+        assert_eq!(
+            SparsnasDecoderError::InvalidCharacter('x').to_string(),
+            "invalid character 'x' in serial number"
+        );
+        assert_eq!(
+            SparsnasDecoderError::BadDigitCount { got: 8 }.to_string(),
+            "expected 9 digits in serial number, got 8"
+        );
+        assert_eq!(
+            SparsnasDecoderError::SerialOutOfRange(0).to_string(),
+            "serial number 0 out of range 100000000..=999999999"
+        );
+    }
+
+    #[test]
+    fn error_display() {
+        let [crc, length, serial, count, unknown] = ERRORS;
+        assert_eq!(crc.to_string(), "CRC mismatch: expected 0x6957, got 0xffff");
+        assert_eq!(
+            length.to_string(),
+            "unexpected packet length: expected 20, got 19"
+        );
+        assert_eq!(
+            serial.to_string(),
+            "serial in packet (565321) does not match configured serial (547040)"
+        );
+        assert_eq!(
+            count.to_string(),
+            "packet sequence mismatch: expected sequence byte 43, got 44"
+        );
+        assert_eq!(
+            unknown.to_string(),
+            "packet is not from any configured transmitter, serial in packet (565321)"
+        );
+        assert_eq!(
+            SparsnasDecodeError::UnknownTransmitter { serial: None }.to_string(),
+            "packet is not from any configured transmitter"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn error_trait() {
+        for err in ERRORS {
+            let err: &dyn std::error::Error = &err;
+            assert!(!err.to_string().is_empty());
+        }
+
+        for err in ERRORS {
+            let expected = err.to_string();
+            let err: &dyn std::error::Error = &DecodeHexError::DecodeError(err);
+            assert_eq!(err.source().map(|e| e.to_string()), Some(expected));
+        }
+        let err: &dyn std::error::Error = &DecodeHexError::InvalidHex;
+        assert!(err.source().is_none());
+    }
+
+    /// The public API available without the `std` feature, with
+    /// signatures. This is built with and without `std`, so anything
+    /// here that becomes `std`-only fails the `--no-default-features`
+    /// tests.
+    #[test]
+    #[allow(clippy::type_complexity)] // the signatures are the point
+    fn no_std_api() {
+        fn display<T: core::fmt::Display + core::fmt::Debug>() {}
+        fn from_str<T: core::str::FromStr<Err = SparsnasDecoderError>>() {}
+        fn iterator<T: Iterator<Item = SparsnasPacket> + core::iter::FusedIterator>() {}
+
+        let _: u32 = TIMER_HZ;
+        let _: u32 = DEFAULT_PULSES_PER_KWH;
+        let _: u32 = KEY_DERIVATION_MAGIC;
+        let _: u8 = DEFAULT_LOW_BATTERY_THRESHOLD;
+        let _: u8 = CRITICAL_BATTERY_THRESHOLD;
+        let _: u16 = TIME_BETWEEN_PULSES_SATURATED;
+        let _: u32 = PULSE_COUNT_RESET_THRESHOLD;
+        let _: fn(&[u8]) -> u16 = crc;
+        let _: fn(u16, u16) -> i32 = seq_distance;
+
+        let _: fn(u32) -> SparsnasDecoder = SparsnasDecoder::new;
+        let _: fn(u32) -> Result<SparsnasDecoder, SparsnasDecoderError> = SparsnasDecoder::try_new;
+        let _: fn(&str) -> Result<SparsnasDecoder, SparsnasDecoderError> =
+            SparsnasDecoder::from_serial_str;
+        let _: fn(u32) -> [u8; 5] = SparsnasDecoder::key_for_serial;
+        let _: fn([u8; 5], u32) -> SparsnasDecoder = SparsnasDecoder::from_key;
+        let _: fn(u32, [u8; 5]) -> SparsnasDecoder = SparsnasDecoder::with_key;
+        let _: fn(SparsnasDecoder, bool) -> SparsnasDecoder = SparsnasDecoder::with_serial_check;
+        let _: fn(SparsnasDecoder, bool) -> SparsnasDecoder = SparsnasDecoder::with_seq_check;
+        let _: fn(SparsnasDecoder, u32) -> SparsnasDecoder = SparsnasDecoder::with_pulses_per_kwh;
+        let _: fn(&SparsnasDecoder) -> u32 = SparsnasDecoder::pulses_per_kwh;
+        let _: fn(&SparsnasDecoder) -> u32 = SparsnasDecoder::serial;
+        let _: fn(&SparsnasDecoder) -> [u8; 5] = SparsnasDecoder::key;
+        let _: fn(&SparsnasDecoder, &SparsnasPacket) -> Option<u32> = SparsnasDecoder::power;
+        let _: fn(&SparsnasDecoder, &SparsnasPacket) -> Option<f64> = SparsnasDecoder::energy_kwh;
+        let _: fn(&SparsnasDecoder, usize, &mut [u8]) = SparsnasDecoder::xor_crypt;
+        let _: fn(&SparsnasDecoder, &[u8; 18]) -> [u8; 18] = SparsnasDecoder::decode_to_plaintext;
+        let _: fn(&SparsnasDecoder, &SparsnasPacket) -> [u8; 17] = SparsnasDecoder::encode_nocrclen;
+        let _: fn(&SparsnasDecoder, &SparsnasPacket) -> [u8; 18] = SparsnasDecoder::encode_nocrc;
+        let _: fn(&SparsnasDecoder, &SparsnasPacket) -> [u8; 20] = SparsnasDecoder::encode;
+        let _: fn(&SparsnasDecoder, &[u8; 17]) -> Result<SparsnasPacket, SparsnasDecodeError> =
+            SparsnasDecoder::decode_nocrclen;
+        let _: fn(&SparsnasDecoder, &[u8; 18]) -> Result<SparsnasPacket, SparsnasDecodeError> =
+            SparsnasDecoder::decode_nocrc;
+        let _: fn(&SparsnasDecoder, &[u8; 19]) -> Result<SparsnasPacket, SparsnasDecodeError> =
+            SparsnasDecoder::decode_fixed;
+        let _: fn(&SparsnasDecoder, &[u8; 20]) -> Result<SparsnasPacket, SparsnasDecodeError> =
+            SparsnasDecoder::decode;
+        let _: fn(&SparsnasDecoder, &[u8]) -> Result<SparsnasPacket, SparsnasDecodeError> =
+            SparsnasDecoder::decode_slice;
+        let _: fn(&SparsnasDecoder, &[u8]) -> Result<SparsnasPacket, SparsnasDecodeError> =
+            SparsnasDecoder::decode_nocrc_slice;
+        let _: fn(&SparsnasDecoder, &str) -> Result<SparsnasPacket, DecodeHexError> =
+            SparsnasDecoder::decode_hex;
+        let _: fn(
+            &SparsnasDecoder,
+            &[u8; 20],
+        ) -> Result<(SparsnasPacket, bool), SparsnasDecodeError> = SparsnasDecoder::decode_any;
+        let _: fn(
+            &SparsnasDecoder,
+            &[u8; 20],
+            (),
+        ) -> Result<DecodedFrame<()>, SparsnasDecodeError> = SparsnasDecoder::decode_with_meta;
+        let _: fn(
+            &SparsnasDecoder,
+            &SparsnasRawFrame,
+        ) -> Result<SparsnasPacket, SparsnasDecodeError> = SparsnasDecoder::decode_frame;
+        let _: for<'a> fn(&'a SparsnasDecoder, &'a [u8]) -> SparsnasPackets<'a> =
+            SparsnasDecoder::packets;
+        let _: fn(&SparsnasDecoder) -> DecoderContext<'_> = SparsnasDecoder::context;
+        // impl lifetimes can not be generic in fn pointers
+        let _: fn(DecoderContext<'static>, &'static [u8]) -> DecoderInput<'static> =
+            DecoderContext::with_data;
+        let _: fn(DecoderContext<'static>, &[u8]) -> Result<SparsnasPacket, SparsnasDecodeError> =
+            DecoderContext::decode;
+        let _: fn(DecoderInput<'static>) -> Result<SparsnasPacket, SparsnasDecodeError> =
+            SparsnasPacket::try_from;
+        from_str::<SparsnasDecoder>();
+        let _: fn(&'static str) -> Result<SparsnasDecoder, SparsnasDecoderError> =
+            SparsnasDecoder::try_from;
+        iterator::<SparsnasPackets<'_>>();
+
+        let _: fn() -> SparsnasPacketBuilder = SparsnasPacket::builder;
+        let _: fn() -> SparsnasPacketBuilder = SparsnasPacketBuilder::new;
+        let _: fn(SparsnasPacketBuilder) -> SparsnasPacket = SparsnasPacketBuilder::build;
+        let _: fn(&SparsnasPacket) -> [u8; 18] = SparsnasPacket::raw_plaintext;
+        let _: fn(&SparsnasPacket, u32) -> SparsnasPacketDisplay<'_> = SparsnasPacket::display_with;
+        let _: fn(&SparsnasPacket, &SparsnasPacket) -> bool = SparsnasPacket::is_newer_than;
+        let _: fn(&SparsnasPacket, &SparsnasPacket) -> bool = SparsnasPacket::is_duplicate_of;
+        let _: fn(&SparsnasPacket) -> SparsnasStatus = SparsnasPacket::status_flags;
+        let _: fn(&SparsnasPacket) -> u8 = SparsnasPacket::battery_percentage_clamped;
+        let _: fn(&SparsnasPacket) -> BatteryState = SparsnasPacket::battery_state;
+        let _: fn(&SparsnasPacket) -> bool = SparsnasPacket::battery_is_low;
+        let _: fn(&SparsnasPacket, u8) -> bool = SparsnasPacket::battery_is_low_threshold;
+        let _: fn(&SparsnasPacket) -> bool = SparsnasPacket::battery_is_critical;
+        let _: fn(&SparsnasPacket) -> bool = SparsnasPacket::is_standby;
+        let _: fn(&SparsnasPacket) -> bool = SparsnasPacket::is_idle;
+        let _: fn(&SparsnasPacket) -> bool = SparsnasPacket::has_recent_pulse;
+        let _: fn(&SparsnasPacket, u32) -> Option<u32> = SparsnasPacket::power;
+        let _: fn(&SparsnasPacket, u32, Rounding) -> Option<u32> = SparsnasPacket::power_with;
+        let _: fn(&SparsnasPacket, u32) -> Option<f64> = SparsnasPacket::power_f64;
+        let _: fn(&SparsnasPacket, u32) -> Option<u64> = SparsnasPacket::power_milliwatts;
+        let _: fn(&SparsnasPacket) -> f64 = SparsnasPacket::time_between_pulses_secs;
+        let _: fn(&SparsnasPacket, u32) -> Option<f64> = SparsnasPacket::energy_kwh;
+        let _: fn(&SparsnasPacket, u32) -> Option<u32> = SparsnasPacket::energy_kwh_u32;
+        let _: fn(&SparsnasPacket, u32) -> Option<u64> = SparsnasPacket::energy_wh;
+        let _: fn(&SparsnasPacket, &SparsnasPacket) -> u64 = SparsnasPacket::delta_pulse_count;
+        let _: fn(&SparsnasPacket, &SparsnasPacket, u32) -> Option<f64> =
+            SparsnasPacket::consumption_since;
+        let _: fn(&SparsnasPacket, &SparsnasPacket) -> Option<u32> = SparsnasPacket::pulse_delta;
+        let _: fn(&SparsnasPacket, &SparsnasPacket, u32) -> Option<u64> =
+            SparsnasPacket::energy_delta_wh;
+        display::<SparsnasPacket>();
+        display::<SparsnasPacketDisplay<'_>>();
+
+        let _: fn(u16) -> SparsnasStatus = SparsnasStatus::new;
+        let _: fn(&SparsnasStatus) -> u16 = SparsnasStatus::raw;
+        let _: fn(&SparsnasStatus) -> u8 = SparsnasStatus::high;
+        let _: fn(&SparsnasStatus) -> u8 = SparsnasStatus::low;
+        let _: fn(&SparsnasStatus, u32) -> bool = SparsnasStatus::bit;
+        let _: fn(&SparsnasStatus, u16) -> bool = SparsnasStatus::contains;
+
+        let _: fn(&[u8]) -> Result<SparsnasRawFrame, SparsnasDecodeError> = SparsnasRawFrame::parse;
+        let _: fn(&SparsnasRawFrame) -> &[u8; 18] = SparsnasRawFrame::data;
+        let _: fn(&FrameField) -> core::ops::Range<usize> = FrameField::range;
+        let _: fn(&FrameField) -> &'static str = FrameField::name;
+
+        let _: fn(SparsnasDecoder) -> SparsnasSession = SparsnasSession::new;
+        let _: fn(
+            &mut SparsnasSession,
+            &[u8; 20],
+        ) -> Result<(SparsnasPacket, u16), SparsnasDecodeError> = SparsnasSession::decode;
+        let _: fn(&mut SparsnasSession, &SparsnasPacket) -> SeqStatus = SparsnasSession::observe;
+        let _: fn(&SparsnasSession, &SparsnasPacket) -> bool = SparsnasSession::detect_reset;
+        let _: fn(&SparsnasSession) -> u64 = SparsnasSession::missed_packets;
+        let _: fn(&mut SparsnasSession) = SparsnasSession::reset;
+
+        let _: fn() -> DuplicateFilter<4> = DuplicateFilter::new;
+        let _: fn(&mut DuplicateFilter<4>, &SparsnasPacket) -> bool = DuplicateFilter::accept;
+        let _: fn(&mut DuplicateFilter<4>) = DuplicateFilter::clear;
+
+        display::<SparsnasDecodeError>();
+        display::<DecodeHexError>();
+        display::<SparsnasDecoderError>();
+    }
+
+    /// The public API that is only available with the `std` feature,
+    /// as listed in the README.
+    #[cfg(feature = "std")]
+    #[test]
+    #[allow(clippy::type_complexity)] // the signatures are the point
+    fn std_api() {
+        fn error<T: std::error::Error>() {}
+
+        error::<SparsnasDecodeError>();
+        error::<DecodeHexError>();
+        error::<SparsnasDecoderError>();
+
+        let _: fn(&SparsnasDecoder, &[u8]) -> Vec<SparsnasPacket> = SparsnasDecoder::decode_stream;
+        let _: fn(&SparsnasDecoder, &[u8]) -> Vec<(usize, SparsnasPacket)> =
+            SparsnasDecoder::decode_all;
+        let _: fn(&SparsnasDecoder, &[u8]) -> Decoded = SparsnasDecoder::decode_lenient;
+        let _: fn(&Decoded) -> bool = Decoded::is_ok;
+        let _: fn(&[[u8; 20]]) -> Vec<u32> = SparsnasDecoder::recover_serial;
+        let _: fn(&[u8; 20], u32) -> Vec<u32> = SparsnasDecoder::recover_serial_with_prefix;
+        let _: fn(u32, &[u8; 20], &[[u8; 20]]) -> Vec<u32> =
+            SparsnasDecoder::find_serial_with_suffix;
+
+        let _: fn() -> MultiDecoder = MultiDecoder::new;
+        let _: fn(&mut MultiDecoder, u32) -> Result<(), SparsnasDecoderError> =
+            MultiDecoder::add_serial;
+        let _: fn(&mut MultiDecoder, u32) = MultiDecoder::remove_serial;
+        let _: fn(&MultiDecoder, &[u8; 20]) -> Result<(u32, SparsnasPacket), SparsnasDecodeError> =
+            MultiDecoder::decode;
+        let _: MultiDecoder = [400_565_321].into_iter().collect();
+
+        let _: fn(usize) -> PowerAverager = PowerAverager::new;
+        let _: fn(&mut PowerAverager, &SparsnasPacket) = PowerAverager::push;
+        let _: fn(&mut PowerAverager) = PowerAverager::clear;
+        let _: fn(&PowerAverager, u32) -> Option<f64> = PowerAverager::average_power;
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json() {
+        let pkt = REAL_PACKET;
+
+        let json = serde_json::to_string(&pkt).unwrap();
+        assert!(json.contains("\"time_between_pulses\":1998"));
+        assert_eq!(serde_json::from_str::<SparsnasPacket>(&json).unwrap(), pkt);
+
+        for err in ERRORS {
+            let json = serde_json::to_string(&err).unwrap();
+            assert_eq!(
+                serde_json::from_str::<SparsnasDecodeError>(&json).unwrap(),
+                err
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_bincode() {
+        let pkt = SparsnasPacket::builder()
+            .packet_seq(0xffff)
+            .time_between_pulses(0xffff)
+            .pulse_count(u32::MAX)
+            .battery_percentage(0xff)
+            .status(0xffff)
+            .serial(999999)
+            .raw_byte1(0xff)
+            .build();
+
+        let bin = bincode::serialize(&pkt).unwrap();
+        assert_eq!(bincode::deserialize::<SparsnasPacket>(&bin).unwrap(), pkt);
+
+        let err = SparsnasDecodeError::BadSerial {
+            expected: 547040,
+            got: 565321,
+        };
+        let bin = bincode::serialize(&err).unwrap();
+        assert_eq!(
+            bincode::deserialize::<SparsnasDecodeError>(&bin).unwrap(),
+            err
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut pkt = KODARN_PACKET;
+
+        assert_eq!(
+            pkt.to_string(),
+            "seq=36 power=60W interval=59953ms pulses=9 battery=100% status=0x40c1 serial=565321"
+        );
+        assert_eq!(pkt.display_with(1000).to_string(), pkt.to_string());
+        assert_eq!(
+            pkt.display_with(2000).to_string(),
+            "seq=36 power=30W interval=59953ms pulses=9 battery=100% status=0x40c1 serial=565321"
+        );
+        assert_eq!(
+            pkt.display_with(0).to_string(),
+            "seq=36 interval=59953ms pulses=9 battery=100% status=0x40c1 serial=565321"
+        );
+        assert_eq!(
+            format!("{pkt:#}"),
+            "seq:      36\n\
+             power:    60 W\n\
+             interval: 59953 ms\n\
+             pulses:   9\n\
+             battery:  100%\n\
+             status:   0x40c1\n\
+             serial:   565321"
+        );
+
+        pkt.time_between_pulses = 0;
+        assert_eq!(
+            pkt.to_string(),
+            "seq=36 interval=0ms pulses=9 battery=100% status=0x40c1 serial=565321"
+        );
+        assert_eq!(
+            format!("{pkt:#}"),
+            "seq:      36\n\
+             interval: 0 ms\n\
+             pulses:   9\n\
+             battery:  100%\n\
+             status:   0x40c1\n\
+             serial:   565321"
+        );
+    }
+
+    #[test]
+    fn display_real() {
+        let d = SparsnasDecoder::new(REAL_SERIAL);
+        let pkt = d.decode(&REAL).unwrap();
 
-        assert_eq!(res, Err(SparsnasDecodeError::BadCRC));
+        let line = pkt.to_string();
+        assert!(!line.contains('\n'));
+        assert!(line.contains("serial=547040"), "{line}");
+        assert!(line.contains("power=1845W"), "{line}");
     }
 }
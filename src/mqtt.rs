@@ -0,0 +1,74 @@
+//! Formatting packets as MQTT payloads.
+
+use std::fmt::Write;
+
+use crate::SparsnasPacket;
+
+impl SparsnasPacket {
+    /// Format the packet as a JSON object for publishing over MQTT,
+    /// e.g `{"power_w":1845,"pulse_count":4555342,"battery_pct":100,"seq":20395}`.
+    ///
+    /// `power_w` is `null` if there is no power, see
+    /// [SparsnasPacket::power].
+    ///
+    /// Requires the `mqtt` feature.
+    pub fn to_mqtt_payload(&self, pulses_per_kwh: u32) -> Vec<u8> {
+        let mut json = String::from("{\"power_w\":");
+        match self.power(pulses_per_kwh) {
+            Some(power) => write!(json, "{power}").unwrap(),
+            None => json.push_str("null"),
+        }
+        write!(
+            json,
+            ",\"pulse_count\":{},\"battery_pct\":{},\"seq\":{}}}",
+            self.pulse_count, self.battery_percentage, self.packet_seq
+        )
+        .unwrap();
+        json.into_bytes()
+    }
+
+    /// Topic to publish the packet on, below some prefix, e.g
+    /// `sparsnas/547040`.
+    ///
+    /// Requires the `mqtt` feature.
+    pub fn mqtt_topic_suffix(&self) -> String {
+        format!("sparsnas/{}", self.serial)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+
+    #[test]
+    fn mqtt_payload() {
+        let mut pkt = REAL_PACKET;
+
+        assert_eq!(
+            pkt.to_mqtt_payload(1000),
+            br#"{"power_w":1845,"pulse_count":4555342,"battery_pct":100,"seq":20395}"#
+        );
+        assert_eq!(pkt.mqtt_topic_suffix(), "sparsnas/547040");
+
+        pkt.time_between_pulses = 0xffff;
+        assert_eq!(
+            pkt.to_mqtt_payload(1000),
+            br#"{"power_w":0,"pulse_count":4555342,"battery_pct":100,"seq":20395}"#
+        );
+
+        pkt.pulse_count = 0;
+        assert_eq!(
+            pkt.to_mqtt_payload(1000),
+            br#"{"power_w":null,"pulse_count":0,"battery_pct":100,"seq":20395}"#
+        );
+    }
+
+    #[test]
+    fn mqtt_payload_is_json() {
+        let pkt = KODARN_PACKET;
+
+        let value: serde_json::Value = serde_json::from_slice(&pkt.to_mqtt_payload(1000)).unwrap();
+        assert_eq!(value["power_w"], 60);
+        assert_eq!(value["seq"], 36);
+    }
+}
@@ -0,0 +1,187 @@
+//! Decoding packets from several transmitters.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    SparsnasDecodeError, SparsnasDecoder, SparsnasDecoderError, SparsnasPacket, SparsnasRawFrame,
+};
+
+/// Holds decoders for several serial numbers, and decodes packets
+/// from any of the transmitters.
+#[derive(Debug, Clone, Default)]
+pub struct MultiDecoder {
+    decoders: BTreeMap<u32, SparsnasDecoder>,
+}
+
+impl MultiDecoder {
+    /// Create a new decoder without any serial numbers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a decoder for the specified serial number.
+    ///
+    /// Returns an error, and adds nothing, if the serial number is out
+    /// of range, see [SparsnasDecoder::try_new].
+    pub fn add_serial(&mut self, serial: u32) -> Result<(), SparsnasDecoderError> {
+        self.decoders
+            .insert(serial, SparsnasDecoder::try_new(serial)?);
+        Ok(())
+    }
+
+    /// Remove the decoder for the specified serial number.
+    pub fn remove_serial(&mut self, serial: u32) {
+        self.decoders.remove(&serial);
+    }
+
+    /// The serial numbers added, in ascending order.
+    pub fn serials(&self) -> impl Iterator<Item = u32> + '_ {
+        self.decoders.keys().copied()
+    }
+
+    /// Decode a packet, trying the decoders in order of serial
+    /// number. Returns the serial number of the decoder that decoded
+    /// the packet together with the packet.
+    ///
+    /// The length and CRC are checked once, and errors from that are
+    /// returned as is. If the packet is valid but no decoder matches,
+    /// [SparsnasDecodeError::UnknownTransmitter] is returned, with
+    /// the serial number decrypted by the first decoder which got a
+    /// consistent sequence number.
+    pub fn decode(&self, data: &[u8; 20]) -> Result<(u32, SparsnasPacket), SparsnasDecodeError> {
+        let frame = SparsnasRawFrame::parse(data)?;
+
+        let mut found = None;
+        for (serial, d) in &self.decoders {
+            match d.decode_frame(&frame) {
+                Ok(pkt) => return Ok((*serial, pkt)),
+                Err(SparsnasDecodeError::BadSerial { got, .. }) => {
+                    found.get_or_insert(got);
+                }
+                Err(_) => {}
+            }
+        }
+        Err(SparsnasDecodeError::UnknownTransmitter { serial: found })
+    }
+}
+
+impl FromIterator<u32> for MultiDecoder {
+    /// Create a decoder for all the serial numbers.
+    ///
+    /// Panics if a serial number is out of range, see
+    /// [SparsnasDecoder::new]. Use [MultiDecoder::add_serial] to
+    /// handle that.
+    fn from_iter<I: IntoIterator<Item = u32>>(serials: I) -> Self {
+        let mut m = MultiDecoder::new();
+        for serial in serials {
+            m.decoders.insert(serial, SparsnasDecoder::new(serial));
+        }
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+    use crate::*;
+
+    #[test]
+    fn multi() {
+        let mut m = MultiDecoder::new();
+        m.add_serial(KODARN_SERIAL).unwrap();
+        m.add_serial(REAL_SERIAL).unwrap();
+
+        assert_eq!(m.decode(&KODARN), Ok((KODARN_SERIAL, KODARN_PACKET)));
+        assert_eq!(m.decode(&REAL), Ok((REAL_SERIAL, REAL_PACKET)));
+
+        m.remove_serial(REAL_SERIAL);
+        assert!(m.decode(&REAL).is_err());
+        assert!(m.decode(&KODARN).is_ok());
+    }
+
+    #[test]
+    fn own_serial_only() {
+        for (serial, frame) in [(KODARN_SERIAL, KODARN), (REAL_SERIAL, REAL)] {
+            let other = if serial == KODARN_SERIAL {
+                REAL_SERIAL
+            } else {
+                KODARN_SERIAL
+            };
+
+            let mut m = MultiDecoder::new();
+            m.add_serial(serial).unwrap();
+            m.add_serial(other).unwrap();
+            assert_eq!(m.decode(&frame).map(|(s, _)| s), Ok(serial));
+
+            let mut m = MultiDecoder::new();
+            m.add_serial(other).unwrap();
+            assert!(matches!(
+                m.decode(&frame),
+                Err(SparsnasDecodeError::UnknownTransmitter { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn from_iter() {
+        let m: MultiDecoder = [KODARN_SERIAL, REAL_SERIAL].into_iter().collect();
+
+        assert_eq!(
+            m.serials().collect::<Vec<_>>(),
+            [REAL_SERIAL, KODARN_SERIAL]
+        );
+        assert_eq!(m.decode(&KODARN).map(|(s, _)| s), Ok(KODARN_SERIAL));
+        assert_eq!(m.decode(&REAL).map(|(s, _)| s), Ok(REAL_SERIAL));
+    }
+
+    #[test]
+    fn add_serial_out_of_range() {
+        let mut m = MultiDecoder::new();
+
+        for serial in [0, 40_056_532, 1_000_000_000] {
+            assert_eq!(
+                m.add_serial(serial),
+                Err(SparsnasDecoderError::SerialOutOfRange(serial))
+            );
+        }
+        assert_eq!(m.serials().count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_iter_out_of_range() {
+        let _: MultiDecoder = [KODARN_SERIAL, 40_056_532].into_iter().collect();
+    }
+
+    #[test]
+    fn no_match() {
+        let mut m = MultiDecoder::new();
+
+        assert_eq!(
+            m.decode(&KODARN),
+            Err(SparsnasDecodeError::UnknownTransmitter { serial: None })
+        );
+
+        let mut bad = KODARN;
+        bad[19] ^= 1;
+        assert!(matches!(
+            m.decode(&bad),
+            Err(SparsnasDecodeError::BadCRC { .. })
+        ));
+
+        m.add_serial(REAL_SERIAL).unwrap();
+        assert!(matches!(
+            m.decode(&bad),
+            Err(SparsnasDecodeError::BadCRC { .. })
+        ));
+
+        // the key for 400547040 gives a consistent sequence number
+        // for this packet, but another serial number
+        assert_eq!(
+            m.decode(&KODARN),
+            Err(SparsnasDecodeError::UnknownTransmitter {
+                serial: Some(6514505)
+            })
+        );
+    }
+}
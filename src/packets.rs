@@ -0,0 +1,87 @@
+//! Iterating over packets in a buffer.
+
+use crate::{SparsnasDecoder, SparsnasPacket};
+
+/// Iterator over the packets found in a buffer, created by
+/// [SparsnasDecoder::packets].
+#[derive(Debug, Clone)]
+pub struct SparsnasPackets<'a> {
+    decoder: &'a SparsnasDecoder,
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SparsnasPackets<'a> {
+    pub(crate) fn new(decoder: &'a SparsnasDecoder, data: &'a [u8]) -> Self {
+        SparsnasPackets {
+            decoder,
+            data,
+            pos: 0,
+        }
+    }
+
+    /// Find the next packet, returning it together with its offset in
+    /// the buffer.
+    pub(crate) fn next_with_offset(&mut self) -> Option<(usize, SparsnasPacket)> {
+        while self.pos + 20 <= self.data.len() {
+            let offset = self.pos;
+            if self.data[offset] == 17 {
+                if let Ok(pkt) = self.decoder.decode_slice(&self.data[offset..offset + 20]) {
+                    self.pos += 20;
+                    return Some((offset, pkt));
+                }
+            }
+            self.pos += 1;
+        }
+        None
+    }
+}
+
+impl Iterator for SparsnasPackets<'_> {
+    type Item = SparsnasPacket;
+
+    fn next(&mut self) -> Option<SparsnasPacket> {
+        self.next_with_offset().map(|(_, pkt)| pkt)
+    }
+}
+
+impl core::iter::FusedIterator for SparsnasPackets<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+    use crate::*;
+
+    #[test]
+    fn packets() {
+        let testdata = [KODARN, REAL].concat();
+
+        let kodarn = SparsnasDecoder::new(KODARN_SERIAL);
+        let real = SparsnasDecoder::new(REAL_SERIAL);
+
+        let mut it = kodarn.packets(&testdata);
+        assert_eq!(it.next().map(|p| p.serial), Some(565321));
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next(), None);
+
+        let mut it = real.packets(&testdata);
+        assert_eq!(it.next().map(|p| p.serial), Some(547040));
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next(), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn decode_all() {
+        // false starts overlapping the first frame, then a burst of
+        // the same frame
+        let testdata = [&[0x00, 0x11, 0x11][..], &KODARN, &KODARN, &[0x11, 0x49]].concat();
+
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        let pkt = d.decode(&KODARN).unwrap();
+
+        assert_eq!(d.decode_all(&testdata), [(3, pkt), (23, pkt)]);
+        assert_eq!(d.decode_all(&testdata[4..]), [(19, pkt)]);
+        assert_eq!(d.decode_all(&[]), []);
+    }
+}
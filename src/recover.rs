@@ -0,0 +1,205 @@
+//! Recovering the serial number of a transmitter from captured
+//! packets.
+
+use crate::{SparsnasDecoder, SparsnasRawFrame};
+
+impl SparsnasDecoder {
+    /// Find the serial numbers which could have sent all of `frames`,
+    /// i.e for which [SparsnasDecoder::decode] succeeds on every
+    /// frame. Returns the candidates in ascending order, or nothing
+    /// if `frames` is empty.
+    ///
+    /// The last byte of the serial number field is encrypted with a
+    /// fixed key byte, which gives the lowest byte of the last 6
+    /// digits without knowing the key. That leaves about 3.5 million
+    /// serial numbers to try.
+    ///
+    /// Usually more than one candidate is found, also with many
+    /// frames. Only a few bits of the key are checked through the
+    /// sequence byte, so serial numbers with keys that differ in
+    /// other bits decode the same frames to packets with different
+    /// power, pulse count etc. Pick the one giving sensible values.
+    pub fn recover_serial(frames: &[[u8; 20]]) -> Vec<u32> {
+        let Some(frames) = parse_frames(frames) else {
+            return Vec::new();
+        };
+        let Some(first) = frames.first() else {
+            return Vec::new();
+        };
+
+        let serials =
+            suffixes(first).flat_map(|suffix| (100..1000).map(move |p| p * 1_000_000 + suffix));
+        let mut candidates = find_serials(&frames, serials);
+        candidates.sort_unstable();
+        candidates
+    }
+
+    /// Find the serial numbers starting with the 3 digits `prefix`
+    /// which could have sent `frame`, e.g for when only the first
+    /// digits on the label can be read. Returns the candidates in
+    /// ascending order, or nothing if `prefix` does not have 3
+    /// digits.
+    ///
+    /// Like for [SparsnasDecoder::recover_serial] the lowest byte of
+    /// the last 6 digits is known from the frame, so only about 4000
+    /// serial numbers are tried, which takes a few milliseconds.
+    ///
+    /// A single frame usually gives several candidates, as described
+    /// for [SparsnasDecoder::recover_serial].
+    pub fn recover_serial_with_prefix(frame: &[u8; 20], prefix: u32) -> Vec<u32> {
+        if !(100..1000).contains(&prefix) {
+            return Vec::new();
+        }
+        let Some(frames) = parse_frames(&[*frame]) else {
+            return Vec::new();
+        };
+        let serials = suffixes(&frames[0]).map(|suffix| prefix * 1_000_000 + suffix);
+        find_serials(&frames, serials)
+    }
+
+    /// Find the full serial numbers ending in `suffix`, i.e the 6
+    /// digits shown on the display unit, which could have sent
+    /// `frame` and all of `extra`. Only the 900 possible first 3
+    /// digits are tried. Returns the candidates in ascending order,
+    /// or nothing if `suffix` has more than 6 digits.
+    ///
+    /// Collisions are possible, as for
+    /// [SparsnasDecoder::recover_serial]. The serial numbers which
+    /// collide get keys that only differ in bits which are not
+    /// checked by decoding, so extra frames from the same transmitter
+    /// rarely rule any of them out. They do rule out a transmitter
+    /// which only decodes `frame` by chance.
+    pub fn find_serial_with_suffix(suffix: u32, frame: &[u8; 20], extra: &[[u8; 20]]) -> Vec<u32> {
+        if suffix >= 1_000_000 {
+            return Vec::new();
+        }
+        let Some(frames) = parse_frames(&[&[*frame], extra].concat()) else {
+            return Vec::new();
+        };
+        let serials = (100..1000).map(|prefix| prefix * 1_000_000 + suffix);
+        find_serials(&frames, serials)
+    }
+}
+
+/// The last 6 digits of serial numbers which could have sent
+/// `frame`. The last byte of the serial number field is encrypted
+/// with the first key byte, which is the same for all serial numbers.
+fn suffixes(frame: &SparsnasRawFrame) -> impl Iterator<Item = u32> {
+    let low = frame.data()[8] ^ SparsnasDecoder::key_for_serial(0)[0];
+    (low as u32..1_000_000).step_by(256)
+}
+
+/// Check length and CRC of all frames, `None` if any of them is bad.
+fn parse_frames(frames: &[[u8; 20]]) -> Option<Vec<SparsnasRawFrame>> {
+    frames
+        .iter()
+        .map(|f| SparsnasRawFrame::parse(f).ok())
+        .collect()
+}
+
+/// Try all `serials`, returning the ones which decode all frames.
+fn find_serials(frames: &[SparsnasRawFrame], serials: impl Iterator<Item = u32>) -> Vec<u32> {
+    serials
+        .filter(|serial| match SparsnasDecoder::try_new(*serial) {
+            Ok(d) => frames.iter().all(|f| d.decode_frame(f).is_ok()),
+            Err(_) => false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+    use crate::*;
+
+    #[test]
+    fn recover_serial() {
+        let found = SparsnasDecoder::recover_serial(&[KODARN]);
+        assert!(found.contains(&KODARN_SERIAL));
+        assert!(found.windows(2).all(|w| w[0] < w[1]));
+        for serial in found {
+            let d = SparsnasDecoder::new(serial);
+            assert_eq!(d.decode(&KODARN).map(|p| p.serial), Ok(serial % 1_000_000));
+        }
+
+        let found = SparsnasDecoder::recover_serial(&[REAL]);
+        assert!(found.contains(&REAL_SERIAL));
+    }
+
+    #[test]
+    fn recover_serial_several_frames() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        let mut pkt = KODARN_PACKET;
+        let mut frames = vec![KODARN];
+        for seq in [37, 200, 4000, 40000] {
+            pkt.packet_seq = seq;
+            frames.push(d.encode(&pkt));
+        }
+        let all = SparsnasDecoder::recover_serial(&frames);
+        assert!(all.contains(&KODARN_SERIAL));
+        assert!(all.len() <= SparsnasDecoder::recover_serial(&[KODARN]).len());
+    }
+
+    #[test]
+    fn find_serial_with_suffix() {
+        let found = SparsnasDecoder::find_serial_with_suffix(565_321, &KODARN, &[]);
+        assert!(found.contains(&KODARN_SERIAL));
+        assert!(found.iter().all(|s| s % 1_000_000 == 565_321));
+        assert!(found.windows(2).all(|w| w[0] < w[1]));
+
+        let all = SparsnasDecoder::recover_serial(&[KODARN]);
+        assert_eq!(
+            found,
+            all.into_iter()
+                .filter(|s| s % 1_000_000 == 565_321)
+                .collect::<Vec<_>>()
+        );
+
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        let mut pkt = KODARN_PACKET;
+        pkt.packet_seq = 37;
+        let extra = d.encode(&pkt);
+        let narrowed = SparsnasDecoder::find_serial_with_suffix(565_321, &KODARN, &[extra]);
+        assert!(narrowed.contains(&KODARN_SERIAL));
+        assert!(narrowed.len() <= found.len());
+
+        assert!(SparsnasDecoder::find_serial_with_suffix(547_040, &KODARN, &[]).is_empty());
+        assert!(SparsnasDecoder::find_serial_with_suffix(565_321, &KODARN, &[REAL]).is_empty());
+        assert!(SparsnasDecoder::find_serial_with_suffix(1_565_321, &KODARN, &[]).is_empty());
+    }
+
+    #[test]
+    fn recover_serial_with_prefix() {
+        let found = SparsnasDecoder::recover_serial_with_prefix(&KODARN, 400);
+        assert!(found.contains(&KODARN_SERIAL));
+        assert!(found.iter().all(|s| s / 1_000_000 == 400));
+        assert!(found.windows(2).all(|w| w[0] < w[1]));
+
+        let all = SparsnasDecoder::recover_serial(&[KODARN]);
+        assert_eq!(
+            found,
+            all.into_iter()
+                .filter(|s| s / 1_000_000 == 400)
+                .collect::<Vec<_>>()
+        );
+
+        let found = SparsnasDecoder::recover_serial_with_prefix(&REAL, 400);
+        assert!(found.contains(&REAL_SERIAL));
+
+        assert!(SparsnasDecoder::recover_serial_with_prefix(&KODARN, 1000).is_empty());
+        assert!(SparsnasDecoder::recover_serial_with_prefix(&KODARN, 40).is_empty());
+        let mut bad = KODARN;
+        bad[19] ^= 1;
+        assert!(SparsnasDecoder::recover_serial_with_prefix(&bad, 400).is_empty());
+    }
+
+    #[test]
+    fn recover_serial_no_match() {
+        assert!(SparsnasDecoder::recover_serial(&[]).is_empty());
+        assert!(SparsnasDecoder::recover_serial(&[KODARN, REAL]).is_empty());
+
+        let mut bad = KODARN;
+        bad[19] ^= 1;
+        assert!(SparsnasDecoder::recover_serial(&[bad]).is_empty());
+    }
+}
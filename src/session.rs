@@ -0,0 +1,254 @@
+//! Stateful decoding, keeping track of missed packets.
+
+use crate::{SparsnasDecodeError, SparsnasDecoder, SparsnasPacket};
+
+/// How much lower the pulse count must be than in the previous packet
+/// for [SparsnasSession::detect_reset] to consider the transmitter
+/// restarted.
+pub const PULSE_COUNT_RESET_THRESHOLD: u32 = 16;
+
+/// Number of packets from sequence number `from` to `to`, taking
+/// wrapping from 65535 to 0 into account. A difference of less than
+/// 32768 forward is positive, anything else is negative, i.e `to` is
+/// before `from`.
+pub fn seq_distance(from: u16, to: u16) -> i32 {
+    to.wrapping_sub(from) as i16 as i32
+}
+
+/// How the sequence number of a packet relates to the previous
+/// packet, see [SparsnasSession::observe].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SeqStatus {
+    /// The packet follows directly after the previous one, or is the
+    /// first packet.
+    InOrder,
+    /// Packets were missed between the previous and this packet.
+    Gap {
+        /// Number of missed packets.
+        missed: u16,
+    },
+    /// Same sequence number as the previous packet.
+    Duplicate,
+    /// The sequence number went backwards, e.g because the
+    /// transmitter restarted.
+    Reset,
+}
+
+/// Wraps a [SparsnasDecoder] and keeps track of the sequence number
+/// of the last decoded packet, to detect missed packets.
+#[derive(Debug, Clone)]
+pub struct SparsnasSession {
+    decoder: SparsnasDecoder,
+    last_seq: Option<u16>,
+    last_pulse_count: Option<u32>,
+    missed: u64,
+}
+
+impl SparsnasSession {
+    /// Create a new session using the specified decoder.
+    pub fn new(decoder: SparsnasDecoder) -> Self {
+        SparsnasSession {
+            decoder,
+            last_seq: None,
+            last_pulse_count: None,
+            missed: 0,
+        }
+    }
+
+    /// Decode a packet. Returns the packet together with the number
+    /// of packets missed since the previously decoded packet.
+    ///
+    /// Only a [SeqStatus::Gap] is counted as missed packets.
+    pub fn decode(
+        &mut self,
+        data: &[u8; 20],
+    ) -> Result<(SparsnasPacket, u16), SparsnasDecodeError> {
+        let pkt = self.decoder.decode(data)?;
+
+        let skipped = match self.observe(&pkt) {
+            SeqStatus::Gap { missed } => missed,
+            _ => 0,
+        };
+
+        Ok((pkt, skipped))
+    }
+
+    /// Compare the sequence number of a packet with the previous one
+    /// and remember it. Missed packets are added to
+    /// [SparsnasSession::missed_packets].
+    ///
+    /// The sequence number wraps from 65535 to 0. A jump forward of
+    /// less than 32768 is counted as a gap, anything else as the
+    /// sequence number going backwards.
+    pub fn observe(&mut self, pkt: &SparsnasPacket) -> SeqStatus {
+        let status = match self.last_seq {
+            None => SeqStatus::InOrder,
+            Some(last) => match seq_distance(last, pkt.packet_seq) {
+                0 => SeqStatus::Duplicate,
+                1 => SeqStatus::InOrder,
+                d if d > 1 => SeqStatus::Gap {
+                    missed: (d - 1) as u16,
+                },
+                _ => SeqStatus::Reset,
+            },
+        };
+
+        if let SeqStatus::Gap { missed } = status {
+            self.missed += missed as u64;
+        }
+        self.last_seq = Some(pkt.packet_seq);
+        self.last_pulse_count = Some(pkt.pulse_count);
+
+        status
+    }
+
+    /// Check if the pulse count of a packet indicates that the
+    /// transmitter has restarted since the previously observed
+    /// packet, i.e. that the pulse count is more than
+    /// [PULSE_COUNT_RESET_THRESHOLD] lower.
+    ///
+    /// The pulse count never decreases while the transmitter is
+    /// running, but a packet received out of order may have a
+    /// slightly lower count, which is not considered a restart.
+    ///
+    /// This does not update the state, call
+    /// [SparsnasSession::observe] for that.
+    pub fn detect_reset(&self, pkt: &SparsnasPacket) -> bool {
+        match self.last_pulse_count {
+            Some(last) => last.saturating_sub(pkt.pulse_count) > PULSE_COUNT_RESET_THRESHOLD,
+            None => false,
+        }
+    }
+
+    /// Total number of packets missed since the session was created
+    /// or reset.
+    pub fn missed_packets(&self) -> u64 {
+        self.missed
+    }
+
+    /// Forget the last seen sequence number and pulse count and reset the missed
+    /// packets counter.
+    pub fn reset(&mut self) {
+        self.last_seq = None;
+        self.last_pulse_count = None;
+        self.missed = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testdata::*;
+    use crate::*;
+
+    #[test]
+    fn missed() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        let mut session = SparsnasSession::new(d);
+        let mut pkt = KODARN_PACKET;
+
+        for (seq, skipped) in [(10, 0), (11, 0), (11, 0), (15, 3), (0xfffe, 0), (1, 2)] {
+            pkt.packet_seq = seq;
+            let (decoded, s) = session.decode(&d.encode(&pkt)).unwrap();
+            assert_eq!(decoded, pkt);
+            assert_eq!(s, skipped);
+        }
+        assert_eq!(session.missed_packets(), 5);
+
+        session.reset();
+        assert_eq!(session.missed_packets(), 0);
+        pkt.packet_seq = 20;
+        assert_eq!(session.decode(&d.encode(&pkt)).unwrap().1, 0);
+    }
+
+    #[test]
+    fn observe() {
+        let mut session = SparsnasSession::new(SparsnasDecoder::new(KODARN_SERIAL));
+        let mut pkt = KODARN_PACKET;
+
+        for (seq, status) in [
+            (100, SeqStatus::InOrder),
+            (101, SeqStatus::InOrder),
+            (105, SeqStatus::Gap { missed: 3 }),
+            (105, SeqStatus::Duplicate),
+            (65534, SeqStatus::Reset),
+            (1, SeqStatus::Gap { missed: 2 }),
+            (0, SeqStatus::Reset),
+        ] {
+            pkt.packet_seq = seq;
+            assert_eq!(session.observe(&pkt), status);
+        }
+        assert_eq!(session.missed_packets(), 5);
+    }
+
+    #[test]
+    fn detect_reset() {
+        let mut session = SparsnasSession::new(SparsnasDecoder::new(KODARN_SERIAL));
+        let mut pkt = KODARN_PACKET;
+
+        for pulse_count in [1000, 1001, 1500, 1490, 2000] {
+            pkt.pulse_count = pulse_count;
+            assert!(!session.detect_reset(&pkt));
+            session.observe(&pkt);
+        }
+
+        for pulse_count in [1900, 10, 0] {
+            pkt.pulse_count = pulse_count;
+            assert!(session.detect_reset(&pkt));
+        }
+
+        session.reset();
+        assert!(!session.detect_reset(&pkt));
+    }
+
+    #[test]
+    fn seq_distance() {
+        for (from, to, distance) in [
+            (10, 10, 0),
+            (10, 11, 1),
+            (11, 10, -1),
+            (0xffff, 0, 1),
+            (0, 0xffff, -1),
+            (0xfff0, 0x0010, 0x20),
+            (0x0010, 0xfff0, -0x20),
+            (0, 0x7fff, 0x7fff),
+            (0, 0x8000, -0x8000),
+            (0x8000, 0, -0x8000),
+            (100, 100 + 0x7fff, 0x7fff),
+        ] {
+            assert_eq!(crate::seq_distance(from, to), distance, "{from} -> {to}");
+        }
+    }
+
+    #[test]
+    fn is_newer_than() {
+        let mut old = SparsnasPacket::builder().packet_seq(0xfffe).build();
+        let mut new = SparsnasPacket::builder().packet_seq(3).build();
+
+        assert!(new.is_newer_than(&old));
+        assert!(!old.is_newer_than(&new));
+        assert!(!old.is_newer_than(&old));
+
+        old.packet_seq = 1000;
+        new.packet_seq = 1000 + 0x8000;
+        assert!(!new.is_newer_than(&old));
+        assert!(!old.is_newer_than(&new));
+    }
+
+    #[test]
+    fn bad_packet() {
+        let d = SparsnasDecoder::new(KODARN_SERIAL);
+        let mut session = SparsnasSession::new(d);
+        let mut pkt = KODARN_PACKET;
+
+        pkt.packet_seq = 10;
+        let mut data = d.encode(&pkt);
+        data[19] ^= 1;
+
+        assert!(matches!(
+            session.decode(&data),
+            Err(SparsnasDecodeError::BadCRC { .. })
+        ));
+        pkt.packet_seq = 12;
+        assert_eq!(session.decode(&d.encode(&pkt)).unwrap().1, 0);
+    }
+}
@@ -0,0 +1,64 @@
+//! Status word of the packet.
+
+/// The status word of a packet, see [crate::SparsnasPacket::status_flags].
+///
+/// The meaning of the individual bits is not known, so only access
+/// to the raw bits is provided. In all samples seen so far the
+/// status word is 0x40c1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SparsnasStatus(u16);
+
+impl SparsnasStatus {
+    /// Create from a raw status word.
+    pub const fn new(raw: u16) -> Self {
+        SparsnasStatus(raw)
+    }
+
+    /// The raw status word.
+    pub const fn raw(&self) -> u16 {
+        self.0
+    }
+
+    /// The high byte, which seems to be constant for a transmitter.
+    pub const fn high(&self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    /// The low byte.
+    pub const fn low(&self) -> u8 {
+        self.0 as u8
+    }
+
+    /// Check if a bit is set, `bit` 0 is the least significant bit.
+    pub const fn bit(&self, bit: u32) -> bool {
+        bit < 16 && self.0 & (1 << bit) != 0
+    }
+
+    /// Check if all bits in `mask` are set.
+    pub const fn contains(&self, mask: u16) -> bool {
+        self.0 & mask == mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::status::*;
+
+    #[test]
+    fn samples() {
+        let status = SparsnasStatus::new(16577);
+
+        assert_eq!(status.raw(), 0x40c1);
+        assert_eq!(status.high(), 0x40);
+        assert_eq!(status.low(), 0xc1);
+        assert!(status.bit(0));
+        assert!(!status.bit(1));
+        assert!(status.bit(14));
+        assert!(!status.bit(15));
+        assert!(!status.bit(16));
+
+        assert!(status.contains(0x4001));
+        assert!(!status.contains(0x4101));
+        assert!(status.contains(0));
+    }
+}
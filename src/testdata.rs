@@ -0,0 +1,62 @@
+//! Packets shared by the tests.
+
+use crate::SparsnasPacket;
+
+/// Serial number of the transmitter in
+/// <https://github.com/kodarn/Sparsnas>.
+pub(crate) const KODARN_SERIAL: u32 = 400_565_321;
+
+/// Frame from kodarn, including CRC.
+pub(crate) const KODARN: [u8; 20] = [
+    0x11, 0x49, 0x24, 0x07, 0x0e, 0xa2, 0x76, 0x17, 0x0e, 0xcf, 0x86, 0x91, 0x67, 0x47, 0xcf, 0xa2,
+    0x77, 0xd3, 0x6e, 0x2d,
+];
+
+/// [KODARN] decrypted, without CRC.
+pub(crate) const KODARN_PLAINTEXT: [u8; 18] = [
+    0x11, 0x49, 0x24, 0x40, 0xc1, 0x00, 0x08, 0xa0, 0x49, 0x00, 0x24, 0xef, 0xd0, 0x00, 0x00, 0x00,
+    0x09, 0x64,
+];
+
+/// [KODARN] decoded.
+pub(crate) const KODARN_PACKET: SparsnasPacket = SparsnasPacket::builder()
+    .packet_seq(36)
+    .time_between_pulses(61392)
+    .pulse_count(9)
+    .battery_percentage(100)
+    .status(16577)
+    .serial(565321)
+    .raw_byte1(0x49)
+    .build();
+
+/// Serial number of a transmitter in real use.
+pub(crate) const REAL_SERIAL: u32 = 400_547_040;
+
+/// Frame from [REAL_SERIAL], including CRC.
+pub(crate) const REAL: [u8; 20] = [
+    0x11, 0xe0, 0x2b, 0x07, 0x0e, 0xa2, 0x1d, 0x28, 0xa7, 0x80, 0x09, 0x12, 0xbe, 0x47, 0x8a, 0x20,
+    0x5b, 0x14, 0x69, 0x57,
+];
+
+/// [REAL] decrypted, without CRC.
+pub(crate) const REAL_PLAINTEXT: [u8; 18] = [
+    0x11, 0xe0, 0x2b, 0x40, 0xc1, 0x00, 0x08, 0x58, 0xe0, 0x4f, 0xab, 0x07, 0xce, 0x00, 0x45, 0x82,
+    0x4e, 0x64,
+];
+
+/// [REAL] decoded, 1845 W at 1000 pulses/kWh.
+pub(crate) const REAL_PACKET: SparsnasPacket = SparsnasPacket::builder()
+    .packet_seq(20395)
+    .time_between_pulses(1998)
+    .pulse_count(4555342)
+    .battery_percentage(100)
+    .status(16577)
+    .serial(547040)
+    .raw_byte1(0xe0)
+    .build();
+
+/// The first 18 bytes of `frame`, for the functions that take a frame
+/// without CRC.
+pub(crate) fn nocrc(frame: &[u8; 20]) -> &[u8; 18] {
+    frame[0..18].try_into().unwrap()
+}